@@ -0,0 +1,205 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::SystemTime;
+
+use crate::config::Config;
+use crate::error::AppError;
+use crate::git::{DiffSettings, FileDiff, GitRepository};
+
+/// Repositories and configs opened by earlier commands, kept in Tauri managed state so a
+/// command reuses them instead of rediscovering the repository and rereading its config.
+/// Cloning shares the same cache.
+#[derive(Clone, Default)]
+pub struct RepoCache {
+    repos: Arc<Mutex<HashMap<PathBuf, CachedRepo>>>,
+    configs: Arc<Mutex<HashMap<Option<PathBuf>, CachedConfig>>>,
+    diff: Arc<Mutex<Option<CachedDiff>>>,
+    changes: Arc<Mutex<Option<CachedChanges>>>,
+}
+
+struct CachedRepo {
+    repo: Arc<Mutex<GitRepository>>,
+    git_dir: PathBuf,
+    stamp: Stamp,
+}
+
+struct CachedConfig {
+    config: Config,
+    layers: Vec<PathBuf>,
+    stamp: Stamp,
+}
+
+/// The last file diff that was over the size limits, kept whole while its hunks are fetched
+/// a page at a time.
+struct CachedDiff {
+    key: DiffKey,
+    diff: FileDiff,
+}
+
+/// Every changed file's changed lines, without context, that moved code in the selected
+/// file is looked for in.
+struct CachedChanges {
+    key: DiffKey,
+    files: Arc<Vec<FileDiff>>,
+}
+
+/// What a file diff (or every changed file's, for an empty `file_path`) is made from: the
+/// file, the options, the commit it is against (for a repository), and the files whose
+/// changes would change it with their modification times. Taken before diffing, so a change
+/// made meanwhile doesn't go unnoticed.
+#[derive(PartialEq)]
+pub(crate) struct DiffKey {
+    repo_path: String,
+    file_path: String,
+    staged: bool,
+    settings: DiffSettings,
+    head: Option<String>,
+    inputs: Vec<PathBuf>,
+    stamp: Stamp,
+}
+
+impl DiffKey {
+    pub(crate) fn new(
+        repo_path: &str,
+        file_path: &str,
+        staged: bool,
+        settings: &DiffSettings,
+        head: Option<String>,
+        inputs: Vec<PathBuf>,
+    ) -> Self {
+        DiffKey {
+            repo_path: repo_path.to_string(),
+            file_path: file_path.to_string(),
+            staged,
+            settings: settings.clone(),
+            head,
+            stamp: Stamp::of(&inputs),
+            inputs,
+        }
+    }
+}
+
+/// Modification times of the files a cache entry was built from (`None` for a missing file),
+/// so the entry can be dropped once any of them changes.
+#[derive(PartialEq, Eq)]
+struct Stamp(Vec<Option<SystemTime>>);
+
+impl Stamp {
+    fn of(paths: &[PathBuf]) -> Self {
+        Stamp(
+            paths
+                .iter()
+                .map(|p| fs::metadata(p).and_then(|m| m.modified()).ok())
+                .collect(),
+        )
+    }
+}
+
+/// libgit2 rereads the index and refs when they change on disk, so a handle only goes stale
+/// when the repository itself is replaced or reconfigured.
+fn repo_stamp(git_dir: &Path) -> Stamp {
+    Stamp::of(&[git_dir.join("HEAD"), git_dir.join("config")])
+}
+
+impl RepoCache {
+    /// Runs `f` with the repository at `repo_path`, opening it on first use or when its
+    /// `.git` directory has changed since. Commands on the same repository take turns.
+    pub fn with_repo<T>(
+        &self,
+        repo_path: &str,
+        f: impl FnOnce(&GitRepository) -> Result<T, AppError>,
+    ) -> Result<T, AppError> {
+        let handle = self.repo(repo_path)?;
+        let repo = lock(&handle);
+        f(&repo)
+    }
+
+    fn repo(&self, repo_path: &str) -> Result<Arc<Mutex<GitRepository>>, AppError> {
+        let key = fs::canonicalize(repo_path)
+            .map_err(|_| AppError::RepoNotFound(repo_path.to_string()))?;
+        let mut repos = lock(&self.repos);
+        if let Some(cached) = repos.get(&key) {
+            if cached.stamp == repo_stamp(&cached.git_dir) {
+                return Ok(cached.repo.clone());
+            }
+            repos.remove(&key);
+        }
+
+        let repo = GitRepository::open(repo_path)?;
+        let git_dir = repo.git_dir().to_path_buf();
+        let cached = CachedRepo {
+            repo: Arc::new(Mutex::new(repo)),
+            stamp: repo_stamp(&git_dir),
+            git_dir,
+        };
+        let handle = cached.repo.clone();
+        repos.insert(key, cached);
+        Ok(handle)
+    }
+
+    /// Loads the config for a repository root (or just the global config), reusing the last
+    /// load while neither config file has changed.
+    pub fn config(&self, repo_root: Option<&Path>) -> Result<Config, AppError> {
+        let key = repo_root.map(Path::to_path_buf);
+        let mut configs = lock(&self.configs);
+        if let Some(cached) = configs.get(&key) {
+            if cached.stamp == Stamp::of(&cached.layers) {
+                return Ok(cached.config.clone());
+            }
+        }
+
+        let layers = Config::layer_paths(repo_root)?;
+        let stamp = Stamp::of(&layers);
+        let config = Config::load(repo_root)?;
+        configs.insert(
+            key,
+            CachedConfig {
+                config: config.clone(),
+                layers,
+                stamp,
+            },
+        );
+        Ok(config)
+    }
+
+    /// Runs `f` with the diff kept for `key`, if it is still the one kept.
+    pub(crate) fn with_diff<T>(&self, key: &DiffKey, f: impl FnOnce(&FileDiff) -> T) -> Option<T> {
+        let cached = lock(&self.diff);
+        cached
+            .as_ref()
+            .filter(|cached| cached.key == *key)
+            .map(|cached| f(&cached.diff))
+    }
+
+    /// Keeps `diff` for `key`, in place of the diff kept before.
+    pub(crate) fn keep_diff(&self, key: DiffKey, diff: FileDiff) {
+        *lock(&self.diff) = Some(CachedDiff { key, diff });
+    }
+
+    /// Every changed file's diff for `key`: the ones kept from the last call if nothing has
+    /// changed since, or else `diff()`'s, which are kept in their place.
+    pub(crate) fn changes(
+        &self,
+        key: DiffKey,
+        diff: impl FnOnce() -> Result<Vec<FileDiff>, AppError>,
+    ) -> Result<Arc<Vec<FileDiff>>, AppError> {
+        if let Some(cached) = lock(&self.changes).as_ref().filter(|c| c.key == key) {
+            return Ok(cached.files.clone());
+        }
+        // Diffed without the lock, so other repositories' diffs don't wait on this one
+        let files = Arc::new(diff()?);
+        *lock(&self.changes) = Some(CachedChanges {
+            key,
+            files: files.clone(),
+        });
+        Ok(files)
+    }
+}
+
+/// A command that panicked mid-way leaves nothing half-written in the cache itself, so
+/// carry on with the poisoned lock's contents.
+fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    mutex.lock().unwrap_or_else(|e| e.into_inner())
+}
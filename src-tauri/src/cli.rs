@@ -0,0 +1,66 @@
+use std::io::Read;
+
+/// Options revu was launched with, e.g. `revu /path/to/repo --wait`.
+#[derive(Debug, Clone, Default)]
+pub struct LaunchOptions {
+    pub repo_path: Option<String>,
+    /// Exit once a review has been exported, so a calling agent can block on revu.
+    pub wait: bool,
+    /// A patch file, or `-` for stdin. Previewed against the repository when one is given
+    /// (`revu . --patch <file>`), otherwise reviewed on its own (`revu -`, `revu review <file>`).
+    pub patch: Option<String>,
+    /// Two directories to compare instead of a repository (`revu compare <left> <right>`).
+    pub compare: Option<(String, String)>,
+    /// `.gitignore`-style patterns left out of a directory comparison (`--exclude <pattern>`).
+    pub excludes: Vec<String>,
+}
+
+impl LaunchOptions {
+    pub fn parse(args: impl IntoIterator<Item = String>) -> Self {
+        let mut options = LaunchOptions::default();
+        let mut args = args.into_iter().skip(1);
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--wait" | "-w" => options.wait = true,
+                "--patch" | "-p" => options.patch = args.next(),
+                "-" if options.repo_path.is_none() => options.patch = Some(arg),
+                "--exclude" | "-x" => options.excludes.extend(args.next()),
+                "review" if options.repo_path.is_none() && options.patch.is_none() => {
+                    options.patch = args.next()
+                }
+                "compare" if options.repo_path.is_none() && options.compare.is_none() => {
+                    if let (Some(left), Some(right)) = (args.next(), args.next()) {
+                        options.compare = Some((left, right));
+                    }
+                }
+                _ if options.repo_path.is_none() && !arg.starts_with('-') => {
+                    options.repo_path = Some(arg)
+                }
+                _ => {}
+            }
+        }
+        options
+    }
+
+    /// A name for the patch: the file's name without extension, or `stdin`.
+    pub fn patch_name(&self) -> Option<String> {
+        let source = self.patch.as_deref()?;
+        if source == "-" {
+            return Some("stdin".to_string());
+        }
+        std::path::Path::new(source)
+            .file_stem()
+            .map(|s| s.to_string_lossy().to_string())
+    }
+
+    /// Reads the patch argument, from stdin when it is `-`.
+    pub fn read_patch(&self) -> Option<std::io::Result<String>> {
+        let source = self.patch.as_deref()?;
+        Some(if source == "-" {
+            let mut text = String::new();
+            std::io::stdin().read_to_string(&mut text).map(|_| text)
+        } else {
+            std::fs::read_to_string(source)
+        })
+    }
+}
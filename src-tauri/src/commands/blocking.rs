@@ -0,0 +1,40 @@
+use crate::error::AppError;
+use crate::requests::{CancelToken, RequestRegistry};
+
+/// Runs libgit2 and filesystem work on the blocking thread pool, so a slow repository
+/// doesn't freeze the window while the command runs.
+pub(crate) async fn run_blocking<T, F>(f: F) -> Result<T, AppError>
+where
+    T: Send + 'static,
+    F: FnOnce() -> Result<T, AppError> + Send + 'static,
+{
+    tauri::async_runtime::spawn_blocking(f)
+        .await
+        .map_err(|e| AppError::Custom(format!("Background task failed: {}", e)))?
+}
+
+/// Like [`run_blocking`], for reads the frontend may supersede. A later request under the
+/// same `request` key cancels this one: `f` should check the token before and between
+/// expensive steps, and a cancelled request fails with [`AppError::Cancelled`] even if its
+/// work completed.
+pub(crate) async fn run_cancellable<T, F>(
+    requests: &RequestRegistry,
+    request: Option<String>,
+    f: F,
+) -> Result<T, AppError>
+where
+    T: Send + 'static,
+    F: FnOnce(&CancelToken) -> Result<T, AppError> + Send + 'static,
+{
+    let token = requests.begin(request.as_deref());
+    let worker = token.clone();
+    let result = run_blocking(move || {
+        // Requests queue behind each other on the pool; skip one that is already stale
+        worker.check()?;
+        f(&worker)
+    })
+    .await;
+    requests.finish(request.as_deref(), &token);
+    token.check()?;
+    result
+}
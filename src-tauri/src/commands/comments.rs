@@ -0,0 +1,173 @@
+use super::blocking::run_blocking;
+use crate::cache::RepoCache;
+use crate::error::AppError;
+use crate::review::replies::{self, IncomingReply};
+use crate::review::{CommentCategory, CommentReply, CommentStore, ReviewComment};
+use tauri::State;
+
+fn open_store(repos: &RepoCache, repo_path: &str) -> Result<CommentStore, AppError> {
+    CommentStore::for_path(repos, repo_path)
+}
+
+#[tauri::command]
+pub async fn list_comments(
+    repos: State<'_, RepoCache>,
+    repo_path: String,
+) -> Result<Vec<ReviewComment>, AppError> {
+    let repos = repos.inner().clone();
+    run_blocking(move || Ok(open_store(&repos, &repo_path)?.comments().to_vec())).await
+}
+
+#[tauri::command]
+pub async fn add_comment(
+    repos: State<'_, RepoCache>,
+    repo_path: String,
+    comment: ReviewComment,
+) -> Result<(), AppError> {
+    let repos = repos.inner().clone();
+    run_blocking(move || {
+        let mut store = open_store(&repos, &repo_path)?;
+        store.add(comment)?;
+        store.save()
+    })
+    .await
+}
+
+#[tauri::command]
+pub async fn update_comment(
+    repos: State<'_, RepoCache>,
+    repo_path: String,
+    id: String,
+    content: String,
+    category: CommentCategory,
+    suggestion: Option<String>,
+) -> Result<ReviewComment, AppError> {
+    let repos = repos.inner().clone();
+    run_blocking(move || {
+        let mut store = open_store(&repos, &repo_path)?;
+        let updated = store.update(&id, content, category, suggestion)?.clone();
+        store.save()?;
+        Ok(updated)
+    })
+    .await
+}
+
+#[tauri::command]
+pub async fn add_comment_reply(
+    repos: State<'_, RepoCache>,
+    repo_path: String,
+    comment_id: String,
+    reply: CommentReply,
+) -> Result<ReviewComment, AppError> {
+    let repos = repos.inner().clone();
+    run_blocking(move || {
+        let mut store = open_store(&repos, &repo_path)?;
+        let updated = store.add_reply(&comment_id, reply)?.clone();
+        store.save()?;
+        Ok(updated)
+    })
+    .await
+}
+
+#[tauri::command]
+pub async fn delete_comment_reply(
+    repos: State<'_, RepoCache>,
+    repo_path: String,
+    comment_id: String,
+    reply_id: String,
+) -> Result<ReviewComment, AppError> {
+    let repos = repos.inner().clone();
+    run_blocking(move || {
+        let mut store = open_store(&repos, &repo_path)?;
+        let updated = store.remove_reply(&comment_id, &reply_id)?.clone();
+        store.save()?;
+        Ok(updated)
+    })
+    .await
+}
+
+#[tauri::command]
+pub async fn resolve_comment(
+    repos: State<'_, RepoCache>,
+    repo_path: String,
+    id: String,
+    resolved: bool,
+) -> Result<ReviewComment, AppError> {
+    let repos = repos.inner().clone();
+    run_blocking(move || {
+        let mut store = open_store(&repos, &repo_path)?;
+        let updated = store.set_resolved(&id, resolved)?.clone();
+        store.save()?;
+        Ok(updated)
+    })
+    .await
+}
+
+#[tauri::command]
+pub async fn delete_comment(
+    repos: State<'_, RepoCache>,
+    repo_path: String,
+    id: String,
+) -> Result<(), AppError> {
+    let repos = repos.inner().clone();
+    run_blocking(move || {
+        let mut store = open_store(&repos, &repo_path)?;
+        store.remove(&id)?;
+        store.save()
+    })
+    .await
+}
+
+#[tauri::command]
+pub async fn clear_comments(
+    repos: State<'_, RepoCache>,
+    repo_path: String,
+) -> Result<(), AppError> {
+    let repos = repos.inner().clone();
+    run_blocking(move || {
+        let mut store = open_store(&repos, &repo_path)?;
+        store.clear();
+        store.save()
+    })
+    .await
+}
+
+/// Moves comments from an older store (the webview's localStorage) into the repository,
+/// skipping any that are already there. Returns the number imported.
+#[tauri::command]
+pub async fn import_comments(
+    repos: State<'_, RepoCache>,
+    repo_path: String,
+    comments: Vec<ReviewComment>,
+) -> Result<usize, AppError> {
+    let repos = repos.inner().clone();
+    run_blocking(move || {
+        let mut store = open_store(&repos, &repo_path)?;
+        let imported = store.import(comments);
+        if imported > 0 {
+            store.save()?;
+        }
+        Ok(imported)
+    })
+    .await
+}
+
+/// Applies agent replies to the given repository's comments, for reply files whose review
+/// wasn't exported through revu (e.g. copied to the clipboard). Returns the number matched.
+#[tauri::command]
+pub async fn apply_agent_replies(
+    repos: State<'_, RepoCache>,
+    repo_path: String,
+    replies: Vec<IncomingReply>,
+) -> Result<usize, AppError> {
+    let repos = repos.inner().clone();
+    run_blocking(move || {
+        let mut store = open_store(&repos, &repo_path)?;
+        let matched = replies::apply_replies(&mut store, &replies);
+        if matched > 0 {
+            store.save()?;
+        }
+        Ok(matched)
+    })
+    .await
+}
@@ -1,8 +1,14 @@
+use super::blocking::run_blocking;
+use crate::cache::RepoCache;
 use crate::error::AppError;
-use crate::git::GitRepository;
+use tauri::State;
 
 #[tauri::command]
-pub fn commit(repo_path: String, message: String) -> Result<String, AppError> {
-    let repo = GitRepository::open(&repo_path)?;
-    repo.commit(&message)
+pub async fn commit(
+    repos: State<'_, RepoCache>,
+    repo_path: String,
+    message: String,
+) -> Result<String, AppError> {
+    let repos = repos.inner().clone();
+    run_blocking(move || repos.with_repo(&repo_path, |repo| repo.commit(&message))).await
 }
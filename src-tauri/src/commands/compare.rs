@@ -0,0 +1,14 @@
+use super::blocking::run_blocking;
+use crate::compare::DirComparison;
+use crate::error::AppError;
+
+/// Starts comparing two directory trees. The returned comparison's `path` is passed as the
+/// repository path to the status, diff, comment and export commands.
+#[tauri::command]
+pub async fn open_comparison(
+    left: String,
+    right: String,
+    excludes: Option<Vec<String>>,
+) -> Result<DirComparison, AppError> {
+    run_blocking(move || DirComparison::create(&left, &right, excludes.unwrap_or_default())).await
+}
@@ -1,22 +1,156 @@
+use super::blocking::{run_blocking, run_cancellable};
+use super::source::ReviewSource;
+use crate::cache::RepoCache;
+use crate::config::DiffConfig;
 use crate::error::AppError;
-use crate::git::{FileDiff, GitRepository};
+use crate::git::{detect_moves, DiffHunk, DiffSettings, FileDiff};
+use crate::requests::RequestRegistry;
+use tauri::State;
 
+/// Lines of a hunk sent by `get_diff_hunk` when the caller doesn't say how many.
+const DEFAULT_HUNK_PAGE: usize = 2000;
+
+/// Diffs one file with `options`, whose unset fields come from git config. Pass a `request`
+/// key (e.g. `"file-diff"`) to have each new diff cancel the one still loading when the user
+/// moves on to another file. A diff over the configured size limits comes back as hunk
+/// summaries unless `full` is set; see `get_diff_hunk`.
 #[tauri::command]
-pub fn get_file_diff(
+#[allow(clippy::too_many_arguments)]
+pub async fn get_file_diff(
+    repos: State<'_, RepoCache>,
+    requests: State<'_, RequestRegistry>,
     repo_path: String,
     file_path: String,
     staged: bool,
-    context_lines: Option<u32>,
-    ignore_whitespace: Option<bool>,
+    options: Option<DiffSettings>,
+    full: Option<bool>,
+    request: Option<String>,
 ) -> Result<FileDiff, AppError> {
-    let repo = GitRepository::open(&repo_path)?;
-    let context = context_lines.unwrap_or(3);
-    let ignore_ws = ignore_whitespace.unwrap_or(false);
-    repo.get_file_diff(&file_path, staged, context, ignore_ws)
+    let repos = repos.inner().clone();
+    let settings = options.unwrap_or_default();
+    run_cancellable(&requests, request, move |token| {
+        ReviewSource::with(&repos, &repo_path, |source| {
+            token.check()?;
+            let key = source.diff_key(&repo_path, &file_path, staged, &settings)?;
+            let mut diff =
+                source.get_file_diff(&repos, &repo_path, &file_path, staged, &settings)?;
+            if !full.unwrap_or(false) {
+                let config = source.config(&repos, &source.root(&repo_path))?;
+                if diff.exceeds(config.diff.large_file_lines, config.diff.large_file_bytes) {
+                    // Kept whole so fetching its hunks doesn't diff the file again
+                    repos.keep_diff(key, diff.clone());
+                    diff.collapse();
+                }
+            }
+            Ok(diff)
+        })
+    })
+    .await
+}
+
+/// Fetches the lines of one hunk of a file's diff that came back as summaries because it was
+/// over the size limits, by the hunk's index in the diff: `limit` lines (2000 by default)
+/// from `offset`, with the count of lines after them in `omittedLines`. The file is only
+/// diffed again if it changed since `get_file_diff`.
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+pub async fn get_diff_hunk(
+    repos: State<'_, RepoCache>,
+    repo_path: String,
+    file_path: String,
+    staged: bool,
+    hunk_index: usize,
+    options: Option<DiffSettings>,
+    offset: Option<usize>,
+    limit: Option<usize>,
+) -> Result<DiffHunk, AppError> {
+    let repos = repos.inner().clone();
+    let settings = options.unwrap_or_default();
+    let offset = offset.unwrap_or(0);
+    let limit = limit.unwrap_or(DEFAULT_HUNK_PAGE).max(1);
+    run_blocking(move || {
+        ReviewSource::with(&repos, &repo_path, |source| {
+            let page = |diff: &FileDiff| {
+                diff.hunks
+                    .get(hunk_index)
+                    .map(|hunk| hunk.page(offset, limit))
+                    .ok_or_else(|| {
+                        AppError::Custom(format!("No hunk {} in {}", hunk_index, file_path))
+                    })
+            };
+            let key = source.diff_key(&repo_path, &file_path, staged, &settings)?;
+            if let Some(hunk) = repos.with_diff(&key, page) {
+                return hunk;
+            }
+            let diff = source.get_file_diff(&repos, &repo_path, &file_path, staged, &settings)?;
+            let hunk = page(&diff);
+            repos.keep_diff(key, diff);
+            hunk
+        })
+    })
+    .await
 }
 
+/// `options` with every field git config decides filled in for the repository (or the
+/// user's config, for a patch review or comparison), so the UI can show what its diffs use.
 #[tauri::command]
-pub fn get_combined_diff(repo_path: String) -> Result<Vec<FileDiff>, AppError> {
-    let repo = GitRepository::open(&repo_path)?;
-    repo.get_combined_diff()
+pub async fn get_diff_settings(
+    repos: State<'_, RepoCache>,
+    repo_path: String,
+    options: Option<DiffSettings>,
+) -> Result<DiffSettings, AppError> {
+    let repos = repos.inner().clone();
+    let settings = options.unwrap_or_default();
+    run_blocking(move || {
+        ReviewSource::with(&repos, &repo_path, |source| {
+            Ok(settings.resolve(source.git_config().as_ref()))
+        })
+    })
+    .await
+}
+
+/// Diffs every changed file. Files over the configured size limits come back as hunk
+/// summaries, as from `get_file_diff`.
+#[tauri::command]
+pub async fn get_combined_diff(
+    repos: State<'_, RepoCache>,
+    requests: State<'_, RequestRegistry>,
+    repo_path: String,
+    options: Option<DiffSettings>,
+    request: Option<String>,
+) -> Result<Vec<FileDiff>, AppError> {
+    let repos = repos.inner().clone();
+    let settings = options.unwrap_or_default();
+    run_cancellable(&requests, request, move |token| {
+        ReviewSource::with(&repos, &repo_path, |source| {
+            // Checked between files, so a superseded diff lets go of the repository early
+            let mut files = Vec::new();
+            source.walk_files(
+                &settings,
+                |_| token.check(),
+                |file| {
+                    token.check()?;
+                    files.push(file);
+                    Ok(())
+                },
+            )?;
+            if source.detects_moves(&settings) {
+                detect_moves(&mut files);
+            }
+            // Collapsed only now, as moves are found from every file's lines
+            let config = source.config(&repos, &source.root(&repo_path))?;
+            for file in &mut files {
+                limit_size(file, &config.diff);
+            }
+            Ok(files)
+        })
+    })
+    .await
+}
+
+/// Collapses a diff over the configured size limits to hunk summaries.
+fn limit_size(diff: &mut FileDiff, limits: &DiffConfig) {
+    if diff.exceeds(limits.large_file_lines, limits.large_file_bytes) {
+        diff.collapse();
+    }
 }
@@ -1,14 +1,20 @@
+use super::blocking::run_blocking;
+use crate::cache::RepoCache;
 use crate::error::AppError;
-use crate::git::GitRepository;
+use tauri::State;
 
 #[tauri::command]
-pub fn discard_file(repo_path: String, file_path: String) -> Result<(), AppError> {
-    let repo = GitRepository::open(&repo_path)?;
-    repo.discard_file(&file_path)
+pub async fn discard_file(
+    repos: State<'_, RepoCache>,
+    repo_path: String,
+    file_path: String,
+) -> Result<(), AppError> {
+    let repos = repos.inner().clone();
+    run_blocking(move || repos.with_repo(&repo_path, |repo| repo.discard_file(&file_path))).await
 }
 
 #[tauri::command]
-pub fn discard_all(repo_path: String) -> Result<(), AppError> {
-    let repo = GitRepository::open(&repo_path)?;
-    repo.discard_all()
+pub async fn discard_all(repos: State<'_, RepoCache>, repo_path: String) -> Result<(), AppError> {
+    let repos = repos.inner().clone();
+    run_blocking(move || repos.with_repo(&repo_path, |repo| repo.discard_all())).await
 }
@@ -0,0 +1,116 @@
+use super::blocking::run_blocking;
+use super::review::{assign_review_id, deliver_review, PreparedReview};
+use super::source::ReviewSource;
+use crate::cache::RepoCache;
+use crate::cli::LaunchOptions;
+use crate::error::AppError;
+use crate::git::DiffSettings;
+use crate::review::{history, ExportFormat, ExportResult, Review, ReviewMetadata, ReviewRecord};
+use serde::Serialize;
+use std::fs;
+use std::time::{SystemTime, UNIX_EPOCH};
+use tauri::{AppHandle, State};
+
+const DAY_MS: u64 = 24 * 60 * 60 * 1000;
+
+/// A past export opened for read-only viewing.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReviewHistoryEntry {
+    #[serde(flatten)]
+    pub record: ReviewRecord,
+    /// The exported document as written, if the file still exists.
+    pub content: Option<String>,
+}
+
+/// Lists past exports of the repository, newest first.
+#[tauri::command]
+pub async fn list_review_history(
+    repos: State<'_, RepoCache>,
+    repo_path: String,
+) -> Result<Vec<ReviewMetadata>, AppError> {
+    let repos = repos.inner().clone();
+    run_blocking(move || {
+        let root = ReviewSource::with(&repos, &repo_path, |source| Ok(source.root(&repo_path)))?;
+        let records = history::list_records(&root)?;
+        Ok(records.into_iter().map(|r| r.metadata).collect())
+    })
+    .await
+}
+
+#[tauri::command]
+pub async fn open_review_history(id: String) -> Result<ReviewHistoryEntry, AppError> {
+    run_blocking(move || {
+        let record = history::load_record(&id)?;
+        let content = record
+            .metadata
+            .paths
+            .iter()
+            .find_map(|path| fs::read_to_string(path).ok());
+        Ok(ReviewHistoryEntry { record, content })
+    })
+    .await
+}
+
+/// Delivers a past review again, in its original format unless another is given.
+/// The comments are sent as they were recorded; they are not re-checked against the tree.
+#[tauri::command]
+pub async fn reexport_review(
+    app: AppHandle,
+    launch: State<'_, LaunchOptions>,
+    repos: State<'_, RepoCache>,
+    id: String,
+    format: Option<ExportFormat>,
+) -> Result<ExportResult, AppError> {
+    let record = history::load_record(&id)?;
+    let repo_path = record.metadata.repository;
+    let format = format.unwrap_or(record.metadata.format);
+    let launch = launch.inner().clone();
+    let repos = repos.inner().clone();
+    run_blocking(move || {
+        let prepared = ReviewSource::with(&repos, &repo_path, |source| {
+            let repo_root = source.root(&repo_path);
+            let config = source.config(&repos, &repo_root)?;
+
+            let mut review = Review::new(record.comments);
+            if source.repo().is_some() {
+                review.repository = Some(config.map_path(&repo_root));
+            }
+            assign_review_id(&mut review, &source.name(&repo_root), &config)?;
+            if format.renderer().needs_diffs() {
+                review = review.with_files(source.files(&DiffSettings::default())?);
+            }
+
+            Ok(PreparedReview::new(source, repo_root, config, review))
+        })?;
+        deliver_review(&app, &launch, &prepared, format)
+    })
+    .await
+}
+
+/// Deletes exports older than `max_age_days` and/or all but the newest `keep`.
+/// Returns the number of exports removed.
+#[tauri::command]
+pub async fn prune_review_history(
+    repos: State<'_, RepoCache>,
+    repo_path: String,
+    max_age_days: Option<u64>,
+    keep: Option<usize>,
+) -> Result<usize, AppError> {
+    if max_age_days.is_none() && keep.is_none() {
+        return Err(AppError::Custom(
+            "Specify a maximum age or a number of reviews to keep".to_string(),
+        ));
+    }
+
+    let repos = repos.inner().clone();
+    run_blocking(move || {
+        let root = ReviewSource::with(&repos, &repo_path, |source| Ok(source.root(&repo_path)))?;
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_millis() as u64;
+        history::prune_records(&root, now, max_age_days.map(|days| days * DAY_MS), keep)
+    })
+    .await
+}
@@ -1,13 +1,32 @@
+mod blocking;
+pub mod comments;
 pub mod commit;
+pub mod compare;
 pub mod diff;
 pub mod discard;
+pub mod history;
+pub mod patch;
 pub mod review;
+mod source;
 pub mod staging;
 pub mod status;
+pub mod suggestions;
+pub mod watch;
 
+pub use comments::{
+    add_comment, add_comment_reply, apply_agent_replies, clear_comments, delete_comment,
+    delete_comment_reply, import_comments, list_comments, resolve_comment, update_comment,
+};
 pub use commit::commit;
-pub use diff::{get_combined_diff, get_file_diff};
+pub use compare::open_comparison;
+pub use diff::{get_combined_diff, get_diff_hunk, get_diff_settings, get_file_diff};
 pub use discard::{discard_all, discard_file};
-pub use review::export_review;
+pub use history::{
+    list_review_history, open_review_history, prune_review_history, reexport_review,
+};
+pub use patch::{apply_patch, import_patch, open_patch_review};
+pub use review::{check_review, export_review, render_review};
 pub use staging::{stage_all, stage_file, unstage_all, unstage_file};
-pub use status::get_status;
+pub use status::{cancel_request, get_status};
+pub use suggestions::{apply_suggestion, undo_suggestion};
+pub use watch::{unwatch_repository, watch_repository, RepoWatchState};
@@ -0,0 +1,89 @@
+use super::blocking::run_blocking;
+use crate::cache::RepoCache;
+use crate::error::AppError;
+use crate::git::{parse_patch, FileDiff, PatchApplyResult, PatchTarget};
+use crate::review::PatchReview;
+use serde::Serialize;
+use std::path::Path;
+use tauri::State;
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ImportedPatch {
+    /// The patch text, passed back to `apply_patch` once reviewed.
+    pub patch: String,
+    pub files: Vec<FileDiff>,
+}
+
+/// Parses a unified diff, given as text or read from a file, for preview.
+#[tauri::command]
+pub async fn import_patch(
+    patch: Option<String>,
+    path: Option<String>,
+) -> Result<ImportedPatch, AppError> {
+    run_blocking(move || {
+        let patch = match (patch, path) {
+            (Some(patch), _) => patch,
+            (None, Some(path)) => std::fs::read_to_string(path)?,
+            (None, None) => return Err(AppError::Custom("No patch given".to_string())),
+        };
+        let files = parse_patch(&patch)?;
+        if files.is_empty() {
+            return Err(AppError::Custom("No changes found in patch".to_string()));
+        }
+        Ok(ImportedPatch { patch, files })
+    })
+    .await
+}
+
+#[tauri::command]
+pub async fn apply_patch(
+    repos: State<'_, RepoCache>,
+    repo_path: String,
+    patch: String,
+    target: PatchTarget,
+) -> Result<PatchApplyResult, AppError> {
+    let repos = repos.inner().clone();
+    run_blocking(move || repos.with_repo(&repo_path, |repo| repo.apply_patch(&patch, target))).await
+}
+
+/// A patch opened for review without a repository. Its `path` is passed as the repository
+/// path to the comment and export commands.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OpenedPatchReview {
+    #[serde(flatten)]
+    pub review: PatchReview,
+    pub patch: String,
+    pub files: Vec<FileDiff>,
+}
+
+/// Opens a unified diff, given as text or read from a file, for review on its own.
+#[tauri::command]
+pub async fn open_patch_review(
+    patch: Option<String>,
+    path: Option<String>,
+) -> Result<OpenedPatchReview, AppError> {
+    run_blocking(move || match (patch, path) {
+        (Some(patch), _) => open_patch("patch", patch),
+        (None, Some(path)) => {
+            let name = Path::new(&path)
+                .file_stem()
+                .map(|s| s.to_string_lossy().to_string())
+                .unwrap_or_else(|| "patch".to_string());
+            open_patch(&name, std::fs::read_to_string(path)?)
+        }
+        (None, None) => Err(AppError::Custom("No patch given".to_string())),
+    })
+    .await
+}
+
+pub(crate) fn open_patch(name: &str, patch: String) -> Result<OpenedPatchReview, AppError> {
+    let review = PatchReview::create(name, &patch)?;
+    let files = parse_patch(&patch)?;
+    Ok(OpenedPatchReview {
+        review,
+        patch,
+        files,
+    })
+}
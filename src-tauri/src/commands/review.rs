@@ -1,34 +1,296 @@
+use super::blocking::{run_blocking, run_cancellable};
+use super::source::ReviewSource;
+use crate::cache::RepoCache;
+use crate::cli::LaunchOptions;
+use crate::config::Config;
 use crate::error::AppError;
-use std::fs;
-use std::path::Path;
+use crate::git::{DiffSettings, GitRepository};
+use crate::requests::RequestRegistry;
+use crate::review::patch_review::sanitize;
+use crate::review::{
+    attach_hunks, attach_hunks_from, check_freshness, deliver_all, history, refused_report,
+    replies, DeliveryContext, ExportFormat, ExportOptions, ExportResult, Review, ReviewComment,
+    ReviewMetadata, ReviewRecord, ReviewRenderer, StaleWarning,
+};
+use std::path::{Path, PathBuf};
 use std::time::{SystemTime, UNIX_EPOCH};
+use tauri::{AppHandle, State};
 
-/// Exports the review markdown to ~/.revu/{repo-name}-{epoch}.md and returns the absolute path.
+/// Checks every comment's snippet against the current file so the UI can warn before exporting.
 #[tauri::command]
-pub async fn export_review(repo_path: String, markdown: String) -> Result<String, AppError> {
-    let home = std::env::var("HOME").map_err(|_| {
-        std::io::Error::new(
-            std::io::ErrorKind::NotFound,
-            "HOME environment variable not set",
-        )
-    })?;
-
-    let revu_dir = Path::new(&home).join(".revu");
-    fs::create_dir_all(&revu_dir)?;
-
-    let repo_name = Path::new(&repo_path)
+pub async fn check_review(
+    repos: State<'_, RepoCache>,
+    requests: State<'_, RequestRegistry>,
+    repo_path: String,
+    comments: Vec<ReviewComment>,
+    request: Option<String>,
+) -> Result<Vec<StaleWarning>, AppError> {
+    let repos = repos.inner().clone();
+    run_cancellable(&requests, request, move |token| {
+        ReviewSource::with(&repos, &repo_path, |source| {
+            token.check()?;
+            let mut review = Review::new(unresolved(comments));
+            match source {
+                ReviewSource::Repo(repo) => {
+                    check_freshness(&mut review, |path, source| repo.read_file(path, source))
+                }
+                ReviewSource::Dirs(dirs) => {
+                    check_freshness(&mut review, |path, source| dirs.read_file(path, source))
+                }
+                // A patch doesn't change under its comments, so there is nothing to go stale
+                ReviewSource::Patch(_) => Ok(Vec::new()),
+            }
+        })
+    })
+    .await
+}
+
+/// Renders the review in the given format without writing it anywhere (used for clipboard copy).
+#[tauri::command]
+pub async fn render_review(
+    repos: State<'_, RepoCache>,
+    repo_path: String,
+    comments: Vec<ReviewComment>,
+    format: Option<ExportFormat>,
+    options: Option<ExportOptions>,
+) -> Result<String, AppError> {
+    let repos = repos.inner().clone();
+    run_blocking(move || {
+        let renderer = format.unwrap_or_default().renderer();
+        ReviewSource::with(&repos, &repo_path, |source| {
+            let repo_root = source.root(&repo_path);
+            let config = source.config(&repos, &repo_root)?;
+            let review = build_review(
+                source,
+                &repo_root,
+                &config,
+                comments,
+                renderer.as_ref(),
+                &options.unwrap_or_default(),
+            )?;
+            Ok(renderer.render(&review))
+        })
+    })
+    .await
+}
+
+/// Renders the review and delivers it to each configured destination (by default
+/// `~/.revu/{repo-name}-{epoch}.{ext}`), reporting how each delivery went.
+#[tauri::command]
+pub async fn export_review(
+    app: AppHandle,
+    launch: State<'_, LaunchOptions>,
+    repos: State<'_, RepoCache>,
+    repo_path: String,
+    comments: Vec<ReviewComment>,
+    format: Option<ExportFormat>,
+    options: Option<ExportOptions>,
+) -> Result<ExportResult, AppError> {
+    let launch = launch.inner().clone();
+    let repos = repos.inner().clone();
+    run_blocking(move || {
+        let renderer = format.unwrap_or_default().renderer();
+        let prepared = ReviewSource::with(&repos, &repo_path, |source| {
+            let repo_root = source.root(&repo_path);
+            let config = source.config(&repos, &repo_root)?;
+            let review = build_review(
+                source,
+                &repo_root,
+                &config,
+                comments,
+                renderer.as_ref(),
+                &options.unwrap_or_default(),
+            )?;
+            if review.is_empty() {
+                return Err(AppError::Custom(
+                    "No unresolved comments to export".to_string(),
+                ));
+            }
+            Ok(PreparedReview::new(source, repo_root, config, review))
+        })?;
+        // Delivery can wait on other processes, so it runs without the repository locked
+        deliver_review(&app, &launch, &prepared, format.unwrap_or_default())
+    })
+    .await
+}
+
+/// A built review with what delivering it needs to know about where it came from, taken
+/// while the repository is locked so the delivery can run without the lock.
+pub(crate) struct PreparedReview {
+    review: Review,
+    repo_root: PathBuf,
+    repo_name: String,
+    config: Config,
+    branch: Option<String>,
+    head: Option<String>,
+}
+
+impl PreparedReview {
+    pub(crate) fn new(
+        source: &ReviewSource,
+        repo_root: PathBuf,
+        config: Config,
+        review: Review,
+    ) -> Self {
+        Self {
+            repo_name: source.name(&repo_root),
+            branch: source.repo().and_then(GitRepository::branch),
+            head: source.repo().and_then(GitRepository::head_commit_id),
+            review,
+            repo_root,
+            config,
+        }
+    }
+}
+
+/// Delivers a built review, records it in the export history and honours `--wait`.
+pub(crate) fn deliver_review(
+    app: &AppHandle,
+    launch: &LaunchOptions,
+    prepared: &PreparedReview,
+    format: ExportFormat,
+) -> Result<ExportResult, AppError> {
+    let PreparedReview {
+        review,
+        repo_root,
+        repo_name,
+        config,
+        branch,
+        head,
+    } = prepared;
+    let renderer = format.renderer();
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default();
+
+    let ctx = DeliveryContext {
+        repo_root,
+        repo_name,
+        epoch: now.as_secs(),
+        extension: renderer.extension(),
+        path_mappings: &config.path_mappings,
+        confined: config.export.confined,
+    };
+    let mut result = deliver_all(&config.export.destinations, &ctx, &renderer.render(review));
+    result
+        .deliveries
+        .extend(config.export.refused.iter().map(refused_report));
+
+    // The review has gone out by now, so a history problem mustn't turn it into a failure
+    if result.any_succeeded() {
+        let created_at = now.as_millis() as u64;
+        let saved = history::save_record(&ReviewRecord {
+            metadata: ReviewMetadata {
+                id: review
+                    .id
+                    .clone()
+                    .unwrap_or_else(|| format!("{}-{}", sanitize(repo_name, "review"), created_at)),
+                repository: repo_root.to_string_lossy().to_string(),
+                repo_name: repo_name.clone(),
+                created_at,
+                branch: branch.clone(),
+                head: head.clone(),
+                format,
+                summary: review.summary(),
+                paths: result
+                    .written
+                    .iter()
+                    .map(|p| p.to_string_lossy().to_string())
+                    .collect(),
+            },
+            comments: review.comments.clone(),
+        });
+        if let Err(e) = saved {
+            eprintln!("Failed to record review in history: {}", e);
+        }
+    }
+
+    // `revu --wait` blocks the calling agent until the review has gone out
+    if launch.wait && result.any_succeeded() {
+        app.exit(0);
+    }
+
+    Ok(result)
+}
+
+/// Resolved threads stay in the store for reference but are never sent to the agent.
+fn unresolved(comments: Vec<ReviewComment>) -> Vec<ReviewComment> {
+    comments.into_iter().filter(|c| !c.resolved).collect()
+}
+
+pub(crate) fn repo_name(repo_root: &Path) -> String {
+    repo_root
         .file_name()
         .map(|n| n.to_string_lossy().to_string())
-        .unwrap_or_else(|| "unknown".to_string());
+        .unwrap_or_else(|| "unknown".to_string())
+}
 
-    let epoch = SystemTime::now()
+/// Gives the review a fresh id and tells the agent where to write its reply. The id names
+/// files under `~/.revu`, so the repository name is reduced to safe characters first.
+pub(crate) fn assign_review_id(
+    review: &mut Review,
+    name: &str,
+    config: &Config,
+) -> Result<(), AppError> {
+    let millis = SystemTime::now()
         .duration_since(UNIX_EPOCH)
         .unwrap_or_default()
-        .as_secs();
+        .as_millis();
+    let id = format!("{}-{}", sanitize(name, "review"), millis);
+    review.reply_path = Some(config.map_path(&replies::reply_path(&id)?));
+    review.id = Some(id);
+    Ok(())
+}
+
+pub(crate) fn repo_root(repo: &GitRepository, repo_path: &str) -> PathBuf {
+    repo.workdir()
+        .map(Path::to_path_buf)
+        .unwrap_or_else(|| PathBuf::from(repo_path))
+}
+
+fn build_review(
+    source: &ReviewSource,
+    repo_root: &Path,
+    config: &Config,
+    comments: Vec<ReviewComment>,
+    renderer: &dyn ReviewRenderer,
+    options: &ExportOptions,
+) -> Result<Review, AppError> {
+    let mut review = Review::new(unresolved(comments));
+    assign_review_id(&mut review, &source.name(repo_root), config)?;
 
-    let filename = format!("{}-{}.md", repo_name, epoch);
-    let output_path = revu_dir.join(filename);
-    fs::write(&output_path, &markdown)?;
+    match source {
+        ReviewSource::Repo(repo) => {
+            review.repository = Some(config.map_path(repo_root));
+            // Files and diffs are read fresh so the export reflects the working tree as it is now
+            check_freshness(&mut review, |path, source| repo.read_file(path, source))?;
+            if options.include_hunks {
+                attach_hunks(&mut review, repo, options.hunk_context_lines)?;
+            }
+        }
+        ReviewSource::Dirs(dirs) => {
+            check_freshness(&mut review, |path, source| dirs.read_file(path, source))?;
+            if options.include_hunks {
+                attach_hunks_from(
+                    &mut review,
+                    &dirs.get_combined_diff(&DiffSettings::default())?,
+                    options.hunk_context_lines,
+                );
+            }
+        }
+        ReviewSource::Patch(patch) => {
+            if options.include_hunks {
+                attach_hunks_from(&mut review, &patch.files()?, options.hunk_context_lines);
+            }
+        }
+    }
+    if renderer.needs_diffs() {
+        review = review.with_files(source.files(&DiffSettings::default())?);
+    }
 
-    Ok(output_path.to_string_lossy().to_string())
+    // Patches address files relative to the repository, so their comments must too
+    if config.export.absolute_paths && !renderer.needs_diffs() && source.repo().is_some() {
+        review.use_absolute_paths();
+    }
+    Ok(review)
 }
@@ -0,0 +1,235 @@
+use super::review::{repo_name, repo_root};
+use crate::cache::{DiffKey, RepoCache};
+use crate::compare::DirComparison;
+use crate::config::Config;
+use crate::error::AppError;
+use crate::git::{detect_moves, DiffSettings, FileDiff, GitRepository, RepositoryStatus};
+use crate::review::PatchReview;
+use std::path::{Path, PathBuf};
+
+/// What a review's comments were left on: a repository, a patch reviewed without one, or two
+/// directory trees. The last two are addressed by their session directory under `~/.revu`,
+/// which the frontend passes wherever it would pass a repository path.
+pub(crate) enum ReviewSource<'a> {
+    Repo(&'a GitRepository),
+    Patch(PatchReview),
+    Dirs(DirComparison),
+}
+
+impl ReviewSource<'_> {
+    /// Runs `f` with the repository at `repo_path` (from the cache), or the patch review or
+    /// comparison whose directory it is.
+    pub(crate) fn with<T>(
+        repos: &RepoCache,
+        repo_path: &str,
+        f: impl FnOnce(&ReviewSource) -> Result<T, AppError>,
+    ) -> Result<T, AppError> {
+        let path = Path::new(repo_path);
+        if let Some(patch) = PatchReview::open(path)? {
+            return f(&ReviewSource::Patch(patch));
+        }
+        if let Some(dirs) = DirComparison::open(path)? {
+            return f(&ReviewSource::Dirs(dirs));
+        }
+        repos.with_repo(repo_path, |repo| f(&ReviewSource::Repo(repo)))
+    }
+
+    pub(crate) fn repo(&self) -> Option<&GitRepository> {
+        match self {
+            Self::Repo(repo) => Some(*repo),
+            _ => None,
+        }
+    }
+
+    /// The directory relative export paths are resolved against.
+    pub(crate) fn root(&self, repo_path: &str) -> PathBuf {
+        match self {
+            Self::Repo(repo) => repo_root(repo, repo_path),
+            Self::Patch(patch) => patch.dir().to_path_buf(),
+            Self::Dirs(dirs) => dirs.dir().to_path_buf(),
+        }
+    }
+
+    /// Loads the config; only repositories have a config of their own.
+    pub(crate) fn config(&self, repos: &RepoCache, root: &Path) -> Result<Config, AppError> {
+        match self {
+            Self::Repo(_) => repos.config(Some(root)),
+            _ => repos.config(None),
+        }
+    }
+
+    /// The name used in review ids and `{repo_name}` path templates.
+    pub(crate) fn name(&self, root: &Path) -> String {
+        match self {
+            Self::Repo(_) => repo_name(root),
+            Self::Patch(patch) => patch.name.clone(),
+            Self::Dirs(dirs) => dirs.name.clone(),
+        }
+    }
+
+    pub(crate) fn get_status(&self) -> Result<RepositoryStatus, AppError> {
+        match self {
+            Self::Repo(repo) => repo.get_status(),
+            Self::Patch(patch) => patch.get_status(),
+            Self::Dirs(dirs) => dirs.get_status(),
+        }
+    }
+
+    /// The git config diff options default from: the repository's, or else the user's.
+    pub(crate) fn git_config(&self) -> Option<git2::Config> {
+        match self {
+            Self::Repo(repo) => repo.git_config(),
+            _ => git2::Config::open_default().ok(),
+        }
+    }
+
+    pub(crate) fn detects_moves(&self, settings: &DiffSettings) -> bool {
+        settings.detects_moves(self.git_config().as_ref())
+    }
+
+    /// A patch can't be re-diffed, so its hunks come back as recorded whatever the options.
+    /// Moved code is looked for across every changed file, whose changes are kept in `repos`
+    /// until one of them changes, so only the first diff after a change diffs them all.
+    pub(crate) fn get_file_diff(
+        &self,
+        repos: &RepoCache,
+        repo_path: &str,
+        file_path: &str,
+        staged: bool,
+        settings: &DiffSettings,
+    ) -> Result<FileDiff, AppError> {
+        let diff = match self {
+            Self::Repo(repo) => repo.get_file_diff(file_path, staged, settings)?,
+            Self::Patch(patch) => patch
+                .files()?
+                .into_iter()
+                .find(|diff| diff.path == file_path)
+                .ok_or_else(|| AppError::Custom(format!("Not in patch: {}", file_path)))?,
+            Self::Dirs(dirs) => dirs.get_file_diff(file_path, settings)?,
+        };
+        if !self.detects_moves(settings) {
+            return Ok(diff);
+        }
+
+        // Only the other files' changed lines matter, not their context
+        let others = DiffSettings {
+            context_lines: Some(0),
+            detect_moves: Some(false),
+            ..settings.clone()
+        };
+        let key = DiffKey::new(
+            repo_path,
+            "",
+            false,
+            &others,
+            self.head(),
+            self.change_inputs()?,
+        );
+        let changes = repos.changes(key, || self.files(&others))?;
+        let mut files: Vec<FileDiff> = changes
+            .iter()
+            .filter(|other| other.path != file_path)
+            .cloned()
+            .collect();
+        let index = files.len();
+        files.push(diff);
+        detect_moves(&mut files);
+        Ok(files.swap_remove(index))
+    }
+
+    /// Identifies a file's diff for keeping it between calls: a repository's changes with its
+    /// HEAD commit, index and (for unstaged changes) working tree file, a comparison's with
+    /// both sides' files. A patch's never change. When moved code is looked for, every other
+    /// changed file counts too.
+    pub(crate) fn diff_key(
+        &self,
+        repo_path: &str,
+        file_path: &str,
+        staged: bool,
+        settings: &DiffSettings,
+    ) -> Result<DiffKey, AppError> {
+        let mut inputs = match self {
+            Self::Repo(repo) => {
+                let mut inputs = vec![repo.git_dir().join("index")];
+                if let (false, Some(workdir)) = (staged, repo.workdir()) {
+                    inputs.push(workdir.join(file_path));
+                }
+                inputs
+            }
+            Self::Patch(_) => Vec::new(),
+            Self::Dirs(dirs) => vec![dirs.left_path(file_path), dirs.right_path(file_path)],
+        };
+        if self.detects_moves(settings) {
+            inputs.extend(self.change_inputs()?);
+        }
+        Ok(DiffKey::new(
+            repo_path,
+            file_path,
+            staged,
+            settings,
+            self.head(),
+            inputs,
+        ))
+    }
+
+    /// The commit a repository's diffs are against.
+    fn head(&self) -> Option<String> {
+        self.repo().and_then(GitRepository::head_commit_id)
+    }
+
+    /// The files every change is diffed from: a repository's index and changed working tree
+    /// files, or both sides of a comparison's changed files. Found from the status, which is
+    /// far cheaper than the diffs.
+    fn change_inputs(&self) -> Result<Vec<PathBuf>, AppError> {
+        if let Self::Patch(_) = self {
+            return Ok(Vec::new());
+        }
+        let status = self.get_status()?;
+        let paths = status.files.iter().map(|file| file.path.as_str());
+        Ok(match self {
+            Self::Repo(repo) => {
+                let workdir = repo.workdir();
+                std::iter::once(repo.git_dir().join("index"))
+                    .chain(paths.filter_map(|path| Some(workdir?.join(path))))
+                    .collect()
+            }
+            Self::Dirs(dirs) => paths
+                .flat_map(|path| [dirs.left_path(path), dirs.right_path(path)])
+                .collect(),
+            Self::Patch(_) => Vec::new(),
+        })
+    }
+
+    /// Every changed file one at a time: `on_start` gets their paths up front, then `on_file`
+    /// each file's diff as it is produced.
+    pub(crate) fn walk_files(
+        &self,
+        settings: &DiffSettings,
+        on_start: impl FnOnce(&[String]) -> Result<(), AppError>,
+        on_file: impl FnMut(FileDiff) -> Result<(), AppError>,
+    ) -> Result<(), AppError> {
+        match self {
+            Self::Repo(repo) => repo.walk_combined_diff(settings, on_start, on_file),
+            Self::Dirs(dirs) => dirs.walk_combined_diff(settings, on_start, on_file),
+            Self::Patch(patch) => {
+                let files = patch.files()?;
+                let paths: Vec<String> = files.iter().map(|f| f.path.clone()).collect();
+                on_start(&paths)?;
+                files.into_iter().try_for_each(on_file)
+            }
+        }
+    }
+
+    /// Every changed file, for formats that embed the full diff.
+    pub(crate) fn files(&self, settings: &DiffSettings) -> Result<Vec<FileDiff>, AppError> {
+        let mut files = match self {
+            Self::Repo(repo) => repo.get_combined_diff(settings)?,
+            Self::Patch(patch) => patch.files()?,
+            Self::Dirs(dirs) => dirs.get_combined_diff(settings)?,
+        };
+        if self.detects_moves(settings) {
+            detect_moves(&mut files);
+        }
+        Ok(files)
+    }
+}
@@ -1,26 +1,36 @@
+use super::blocking::run_blocking;
+use crate::cache::RepoCache;
 use crate::error::AppError;
-use crate::git::GitRepository;
+use tauri::State;
 
 #[tauri::command]
-pub fn stage_file(repo_path: String, file_path: String) -> Result<(), AppError> {
-    let repo = GitRepository::open(&repo_path)?;
-    repo.stage_file(&file_path)
+pub async fn stage_file(
+    repos: State<'_, RepoCache>,
+    repo_path: String,
+    file_path: String,
+) -> Result<(), AppError> {
+    let repos = repos.inner().clone();
+    run_blocking(move || repos.with_repo(&repo_path, |repo| repo.stage_file(&file_path))).await
 }
 
 #[tauri::command]
-pub fn unstage_file(repo_path: String, file_path: String) -> Result<(), AppError> {
-    let repo = GitRepository::open(&repo_path)?;
-    repo.unstage_file(&file_path)
+pub async fn unstage_file(
+    repos: State<'_, RepoCache>,
+    repo_path: String,
+    file_path: String,
+) -> Result<(), AppError> {
+    let repos = repos.inner().clone();
+    run_blocking(move || repos.with_repo(&repo_path, |repo| repo.unstage_file(&file_path))).await
 }
 
 #[tauri::command]
-pub fn stage_all(repo_path: String) -> Result<(), AppError> {
-    let repo = GitRepository::open(&repo_path)?;
-    repo.stage_all()
+pub async fn stage_all(repos: State<'_, RepoCache>, repo_path: String) -> Result<(), AppError> {
+    let repos = repos.inner().clone();
+    run_blocking(move || repos.with_repo(&repo_path, |repo| repo.stage_all())).await
 }
 
 #[tauri::command]
-pub fn unstage_all(repo_path: String) -> Result<(), AppError> {
-    let repo = GitRepository::open(&repo_path)?;
-    repo.unstage_all()
+pub async fn unstage_all(repos: State<'_, RepoCache>, repo_path: String) -> Result<(), AppError> {
+    let repos = repos.inner().clone();
+    run_blocking(move || repos.with_repo(&repo_path, |repo| repo.unstage_all())).await
 }
@@ -1,8 +1,30 @@
+use super::blocking::run_cancellable;
+use super::source::ReviewSource;
+use crate::cache::RepoCache;
 use crate::error::AppError;
-use crate::git::{GitRepository, RepositoryStatus};
+use crate::git::RepositoryStatus;
+use crate::requests::RequestRegistry;
+use tauri::State;
 
 #[tauri::command]
-pub fn get_status(repo_path: String) -> Result<RepositoryStatus, AppError> {
-    let repo = GitRepository::open(&repo_path)?;
-    repo.get_status()
+pub async fn get_status(
+    repos: State<'_, RepoCache>,
+    requests: State<'_, RequestRegistry>,
+    repo_path: String,
+    request: Option<String>,
+) -> Result<RepositoryStatus, AppError> {
+    let repos = repos.inner().clone();
+    run_cancellable(&requests, request, move |token| {
+        ReviewSource::with(&repos, &repo_path, |source| {
+            token.check()?;
+            source.get_status()
+        })
+    })
+    .await
+}
+
+/// Cancels the request running under `request`, if any; it fails with "Request cancelled".
+#[tauri::command]
+pub fn cancel_request(requests: State<'_, RequestRegistry>, request: String) {
+    requests.cancel(&request);
 }
@@ -0,0 +1,52 @@
+use super::blocking::run_blocking;
+use crate::cache::RepoCache;
+use crate::error::AppError;
+use crate::review::{suggestions, CommentStore, ReviewComment};
+use tauri::State;
+
+/// Writes a comment's suggestion into the working-tree file and resolves the comment.
+#[tauri::command]
+pub async fn apply_suggestion(
+    repos: State<'_, RepoCache>,
+    repo_path: String,
+    comment_id: String,
+) -> Result<ReviewComment, AppError> {
+    let repos = repos.inner().clone();
+    run_blocking(move || {
+        repos.with_repo(&repo_path, |repo| {
+            let mut store = CommentStore::open(repo)?;
+            let applied = suggestions::apply_suggestion(repo, store.get(&comment_id)?)?;
+            let updated = store
+                .set_applied_suggestion(&comment_id, Some(applied))?
+                .clone();
+            store.save()?;
+            Ok(updated)
+        })
+    })
+    .await
+}
+
+/// Reverts an applied suggestion and reopens the comment.
+#[tauri::command]
+pub async fn undo_suggestion(
+    repos: State<'_, RepoCache>,
+    repo_path: String,
+    comment_id: String,
+) -> Result<ReviewComment, AppError> {
+    let repos = repos.inner().clone();
+    run_blocking(move || {
+        repos.with_repo(&repo_path, |repo| {
+            let mut store = CommentStore::open(repo)?;
+            let applied = store
+                .get(&comment_id)?
+                .applied_suggestion
+                .clone()
+                .ok_or_else(|| AppError::Custom("Suggestion has not been applied".to_string()))?;
+            suggestions::undo_suggestion(repo, &applied)?;
+            let updated = store.set_applied_suggestion(&comment_id, None)?.clone();
+            store.save()?;
+            Ok(updated)
+        })
+    })
+    .await
+}
@@ -0,0 +1,81 @@
+use super::blocking::run_blocking;
+use super::review::repo_root;
+use crate::cache::RepoCache;
+use crate::error::AppError;
+use crate::git::GitRepository;
+use crate::watcher::{watch_repo, RepoWatcher, WatchEvent};
+use std::sync::{Arc, Mutex};
+use tauri::{AppHandle, Emitter, State};
+
+/// The watcher for the repository open in the UI, if any.
+#[derive(Clone, Default)]
+pub struct RepoWatchState(Arc<Mutex<Option<RepoWatcher>>>);
+
+/// Starts watching a repository for changes made outside revu, replacing any previous watch.
+/// Emits `repo-changed` with the affected paths after each burst of changes and
+/// `repo-settled` once the repository has been quiet for the configured period.
+/// Patch reviews and directory comparisons aren't watched.
+#[tauri::command]
+pub async fn watch_repository(
+    app: AppHandle,
+    state: State<'_, RepoWatchState>,
+    repos: State<'_, RepoCache>,
+    repo_path: String,
+) -> Result<(), AppError> {
+    let state = state.inner().clone();
+    let repos = repos.inner().clone();
+    run_blocking(move || start_watch(app, &state, &repos, repo_path)).await
+}
+
+/// Opening the repository and walking its directories to watch them can take a while in a
+/// large one, so this runs off the main thread.
+fn start_watch(
+    app: AppHandle,
+    state: &RepoWatchState,
+    repos: &RepoCache,
+    repo_path: String,
+) -> Result<(), AppError> {
+    let mut current = state
+        .0
+        .lock()
+        .map_err(|_| AppError::Custom("Watcher state poisoned".to_string()))?;
+    if current
+        .as_ref()
+        .is_some_and(|w| w.repository() == repo_path)
+    {
+        return Ok(());
+    }
+    *current = None;
+
+    // The watcher thread gets a handle of its own rather than holding up commands on the
+    // cached one
+    let Ok(repo) = GitRepository::open(&repo_path) else {
+        return Ok(());
+    };
+    let config = repos.config(Some(&repo_root(&repo, &repo_path)))?;
+    if !config.watch.enabled {
+        return Ok(());
+    }
+
+    *current = Some(watch_repo(&repo_path, repo, &config.watch, move |event| {
+        let _ = match event {
+            WatchEvent::Changed(changed) => app.emit("repo-changed", changed),
+            WatchEvent::Settled(settled) => app.emit("repo-settled", settled),
+        };
+    })?);
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn unwatch_repository(state: State<'_, RepoWatchState>) -> Result<(), AppError> {
+    let state = state.inner().clone();
+    run_blocking(move || {
+        let mut current = state
+            .0
+            .lock()
+            .map_err(|_| AppError::Custom("Watcher state poisoned".to_string()))?;
+        *current = None;
+        Ok(())
+    })
+    .await
+}
@@ -0,0 +1,398 @@
+use git2::{Config, FileMode};
+use ignore::gitignore::{Gitignore, GitignoreBuilder};
+use ignore::WalkBuilder;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+use std::fs::{self, File, Metadata};
+use std::io::Read;
+use std::path::{Path, PathBuf};
+
+use crate::config::revu_dir;
+use crate::error::AppError;
+use crate::git::repository::MAX_UNTRACKED_BYTES;
+use crate::git::text;
+use crate::git::{
+    diff_decoded, DiffSettings, FileContent, FileDiff, FileEntry, FileSource, FileStatus,
+    RepositoryStatus,
+};
+use crate::review::patch_review::{fnv1a, sanitize};
+
+const SESSION_FILE: &str = "session.json";
+
+/// How much of each file is compared at a time when checking whether it changed.
+const COMPARE_CHUNK: u64 = 64 * 1024;
+
+/// Two directory trees on disk compared file by file, for agent runs in copied directories
+/// rather than repositories. Like a patch review, each comparison gets a directory under
+/// `~/.revu/compare` holding its comments, which stands in for the repository path.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DirComparison {
+    pub id: String,
+    pub name: String,
+    /// The original tree, shown as the old side.
+    pub left: String,
+    /// The changed tree, shown as the new side.
+    pub right: String,
+    /// `.gitignore`-style patterns for paths to leave out, on top of each tree's own
+    /// `.gitignore` files.
+    pub excludes: Vec<String>,
+    /// The session directory.
+    pub path: String,
+}
+
+fn compare_dir() -> Result<PathBuf, AppError> {
+    Ok(revu_dir()?.join("compare"))
+}
+
+impl DirComparison {
+    /// Starts (or resumes) comparing two trees. The same pair of directories always gets the
+    /// same id, so comments left on an earlier comparison come back.
+    pub fn create(left: &str, right: &str, excludes: Vec<String>) -> Result<Self, AppError> {
+        let left = canonical_dir(left)?;
+        let right = canonical_dir(right)?;
+        let name = right
+            .file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_else(|| "compare".to_string());
+        let key = format!("{}\0{}", left.display(), right.display());
+        let id = format!(
+            "{}-{:016x}",
+            sanitize(&name, "compare"),
+            fnv1a(key.as_bytes())
+        );
+
+        let dir = compare_dir()?.join(&id);
+        fs::create_dir_all(&dir)?;
+        let comparison = DirComparison {
+            id,
+            name,
+            left: left.to_string_lossy().to_string(),
+            right: right.to_string_lossy().to_string(),
+            excludes,
+            path: dir.to_string_lossy().to_string(),
+        };
+        let json = serde_json::to_string_pretty(&comparison)
+            .map_err(|e| AppError::Custom(format!("Failed to serialise comparison: {}", e)))?;
+        fs::write(dir.join(SESSION_FILE), json)?;
+        Ok(comparison)
+    }
+
+    /// Opens the comparison at `path`, or returns `None` if it isn't one.
+    pub fn open(path: &Path) -> Result<Option<Self>, AppError> {
+        if !path.starts_with(compare_dir()?) {
+            return Ok(None);
+        }
+        match fs::read_to_string(path.join(SESSION_FILE)) {
+            Ok(content) => serde_json::from_str(&content).map(Some).map_err(|e| {
+                AppError::Custom(format!("Corrupt comparison {}: {}", path.display(), e))
+            }),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    pub fn dir(&self) -> &Path {
+        Path::new(&self.path)
+    }
+
+    /// Lists the files that differ between the trees, as unstaged changes.
+    pub fn get_status(&self) -> Result<RepositoryStatus, AppError> {
+        let files = self.changed_files()?;
+        Ok(RepositoryStatus {
+            path: self.path.clone(),
+            branch: None,
+            staged_count: 0,
+            unstaged_count: files.len(),
+            files,
+        })
+    }
+
+    pub fn get_file_diff(
+        &self,
+        file_path: &str,
+        settings: &DiffSettings,
+    ) -> Result<FileDiff, AppError> {
+        let (left, right) = (self.left_path(file_path), self.right_path(file_path));
+        let old = read(&left)?;
+        let new = read(&right)?;
+        let old_mode = file_mode(&left)?;
+        let new_mode = file_mode(&right)?;
+        let old_link_target = link_target(old_mode, old.as_deref());
+        let new_link_target = link_target(new_mode, new.as_deref());
+
+        // Like an untracked file in a repository, never diff more than the cap
+        let too_large = [&old, &new]
+            .into_iter()
+            .flatten()
+            .any(|bytes| bytes.len() as u64 > MAX_UNTRACKED_BYTES);
+        if !too_large {
+            // Neither tree need be a repository, so only the user's own git config applies
+            let mut opts = settings.to_options(Config::open_default().ok().as_ref());
+
+            let old_side = old.as_deref().map(|bytes| (bytes, None));
+            let new_side = new.as_deref().map(|bytes| (bytes, None));
+            if let Some(diff) = diff_decoded(file_path, old_side, new_side, &mut opts)? {
+                return Ok(FileDiff {
+                    old_mode,
+                    new_mode,
+                    old_link_target,
+                    new_link_target,
+                    ..diff
+                });
+            }
+        }
+        Ok(FileDiff {
+            path: file_path.to_string(),
+            old_path: None,
+            status: match (&old, &new) {
+                (None, _) => FileStatus::Added,
+                (_, None) => FileStatus::Deleted,
+                _ => FileStatus::Modified,
+            },
+            hunks: vec![],
+            is_binary: !too_large,
+            language: crate::git::detect_language(file_path),
+            is_large: too_large,
+            encoding: None,
+            has_bom: false,
+            old_mode,
+            new_mode,
+            old_link_target,
+            new_link_target,
+            line_endings_only: false,
+        })
+    }
+
+    pub fn get_combined_diff(&self, settings: &DiffSettings) -> Result<Vec<FileDiff>, AppError> {
+        self.changed_files()?
+            .iter()
+            .map(|file| self.get_file_diff(&file.path, settings))
+            .collect()
+    }
+
+    /// Produces the combined diff one file at a time, like
+    /// `GitRepository::walk_combined_diff`.
+    pub fn walk_combined_diff(
+        &self,
+        settings: &DiffSettings,
+        on_start: impl FnOnce(&[String]) -> Result<(), AppError>,
+        mut on_file: impl FnMut(FileDiff) -> Result<(), AppError>,
+    ) -> Result<(), AppError> {
+        let paths: Vec<String> = self.changed_files()?.into_iter().map(|f| f.path).collect();
+        on_start(&paths)?;
+        for path in &paths {
+            on_file(self.get_file_diff(path, settings)?)?;
+        }
+        Ok(())
+    }
+
+    /// Reads a file from the left tree (`Head`/`Index`, the old side) or the right one
+    /// (`Workdir`).
+    pub fn read_file(&self, file_path: &str, source: FileSource) -> Result<FileContent, AppError> {
+        let path = match source {
+            FileSource::Head | FileSource::Index => self.left_path(file_path),
+            FileSource::Workdir => self.right_path(file_path),
+        };
+        Ok(match read(&path)? {
+            Some(bytes) if bytes.len() as u64 > MAX_UNTRACKED_BYTES => FileContent::TooLarge,
+            Some(bytes) => FileContent::Text(match text::decode(&bytes, None) {
+                Some(decoded) => decoded.text.into_owned(),
+                None => String::from_utf8_lossy(&bytes).into_owned(),
+            }),
+            None => FileContent::Missing,
+        })
+    }
+
+    pub(crate) fn left_path(&self, file_path: &str) -> PathBuf {
+        Path::new(&self.left).join(file_path)
+    }
+
+    pub(crate) fn right_path(&self, file_path: &str) -> PathBuf {
+        Path::new(&self.right).join(file_path)
+    }
+
+    fn changed_files(&self) -> Result<Vec<FileEntry>, AppError> {
+        let left = self.list_files(Path::new(&self.left))?;
+        let right = self.list_files(Path::new(&self.right))?;
+
+        let mut files = Vec::new();
+        for path in left.union(&right) {
+            let status = match (left.contains(path), right.contains(path)) {
+                (true, false) => FileStatus::Deleted,
+                (false, true) => FileStatus::Added,
+                _ if unchanged(&self.left_path(path), &self.right_path(path))? => continue,
+                _ => FileStatus::Modified,
+            };
+            files.push(FileEntry {
+                path: path.clone(),
+                status,
+                staged: false,
+                old_path: None,
+            });
+        }
+        Ok(files)
+    }
+
+    /// Every file and symlink under `root` that isn't excluded, as `/`-separated relative
+    /// paths. Symlinks aren't followed; like git, a link is compared by its target.
+    fn list_files(&self, root: &Path) -> Result<BTreeSet<String>, AppError> {
+        let excludes = self.exclude_matcher(root)?;
+        let walk_root = root.to_path_buf();
+        let walker = WalkBuilder::new(root)
+            .hidden(false)
+            .git_ignore(true)
+            .git_exclude(false)
+            .git_global(false)
+            .require_git(false)
+            .filter_entry(move |entry| {
+                if entry.file_name() == ".git" {
+                    return false;
+                }
+                let is_dir = entry.file_type().is_some_and(|t| t.is_dir());
+                let relative = entry
+                    .path()
+                    .strip_prefix(&walk_root)
+                    .unwrap_or(entry.path());
+                !excludes.matched(relative, is_dir).is_ignore()
+            })
+            .build();
+
+        let mut files = BTreeSet::new();
+        for entry in walker {
+            let entry = entry.map_err(|e| AppError::Custom(e.to_string()))?;
+            if !entry
+                .file_type()
+                .is_some_and(|t| t.is_file() || t.is_symlink())
+            {
+                continue;
+            }
+            if let Ok(relative) = entry.path().strip_prefix(root) {
+                let parts: Vec<_> = relative
+                    .components()
+                    .map(|c| c.as_os_str().to_string_lossy())
+                    .collect();
+                files.insert(parts.join("/"));
+            }
+        }
+        Ok(files)
+    }
+
+    fn exclude_matcher(&self, root: &Path) -> Result<Gitignore, AppError> {
+        let mut builder = GitignoreBuilder::new(root);
+        for pattern in &self.excludes {
+            builder
+                .add_line(None, pattern)
+                .map_err(|e| AppError::Custom(format!("Invalid exclude pattern: {}", e)))?;
+        }
+        builder
+            .build()
+            .map_err(|e| AppError::Custom(format!("Invalid exclude pattern: {}", e)))
+    }
+}
+
+fn canonical_dir(path: &str) -> Result<PathBuf, AppError> {
+    let dir = fs::canonicalize(path)
+        .map_err(|e| AppError::Custom(format!("Cannot open directory {}: {}", path, e)))?;
+    if !dir.is_dir() {
+        return Err(AppError::Custom(format!("Not a directory: {}", path)));
+    }
+    Ok(dir)
+}
+
+/// Whether a path is the same in both trees. The cheap checks come first: mode and size,
+/// then whether both sides were last modified at the same moment (as after `cp -a`); only
+/// then is the content compared.
+fn unchanged(left: &Path, right: &Path) -> Result<bool, AppError> {
+    let (left_meta, right_meta) = (fs::symlink_metadata(left)?, fs::symlink_metadata(right)?);
+    if mode(&left_meta) != mode(&right_meta) || left_meta.len() != right_meta.len() {
+        return Ok(false);
+    }
+    if left_meta.file_type().is_symlink() {
+        return Ok(fs::read_link(left)? == fs::read_link(right)?);
+    }
+    if let (Ok(left_time), Ok(right_time)) = (left_meta.modified(), right_meta.modified()) {
+        if left_time == right_time {
+            return Ok(true);
+        }
+    }
+    same_content(left, right)
+}
+
+/// Compares two files a chunk at a time, so neither is ever held in memory whole.
+fn same_content(left: &Path, right: &Path) -> Result<bool, AppError> {
+    let (mut left, mut right) = (File::open(left)?, File::open(right)?);
+    let (mut left_chunk, mut right_chunk) = (Vec::new(), Vec::new());
+    loop {
+        left_chunk.clear();
+        right_chunk.clear();
+        (&mut left)
+            .take(COMPARE_CHUNK)
+            .read_to_end(&mut left_chunk)?;
+        (&mut right)
+            .take(COMPARE_CHUNK)
+            .read_to_end(&mut right_chunk)?;
+        if left_chunk != right_chunk {
+            return Ok(false);
+        }
+        if left_chunk.is_empty() {
+            return Ok(true);
+        }
+    }
+}
+
+/// The git mode a path would be committed with, which only records whether it is a symlink
+/// or an executable file. `None` if it doesn't exist.
+fn file_mode(path: &Path) -> Result<Option<u32>, AppError> {
+    match fs::symlink_metadata(path) {
+        Ok(metadata) => Ok(Some(mode(&metadata))),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+        Err(e) => Err(e.into()),
+    }
+}
+
+fn mode(metadata: &Metadata) -> u32 {
+    if metadata.file_type().is_symlink() {
+        return u32::from(FileMode::Link);
+    }
+    #[cfg(unix)]
+    let executable = {
+        use std::os::unix::fs::PermissionsExt;
+        metadata.permissions().mode() & 0o111 != 0
+    };
+    #[cfg(not(unix))]
+    let executable = false;
+    if executable {
+        0o100755
+    } else {
+        0o100644
+    }
+}
+
+/// A symlink's target, which git diffs as the link's content.
+fn link_target(mode: Option<u32>, content: Option<&[u8]>) -> Option<String> {
+    content
+        .filter(|_| mode == Some(u32::from(FileMode::Link)))
+        .map(|bytes| String::from_utf8_lossy(bytes).into_owned())
+}
+
+/// Reads a file, or a symlink's target, stopping one byte past `MAX_UNTRACKED_BYTES` so
+/// callers can tell it is too large. Returns `None` if it doesn't exist.
+fn read(path: &Path) -> Result<Option<Vec<u8>>, AppError> {
+    let result = fs::symlink_metadata(path).and_then(|metadata| {
+        if metadata.file_type().is_symlink() {
+            let target = fs::read_link(path)?;
+            return Ok(target.to_string_lossy().into_owned().into_bytes());
+        }
+        let mut bytes = Vec::new();
+        File::open(path)?
+            .take(MAX_UNTRACKED_BYTES + 1)
+            .read_to_end(&mut bytes)?;
+        Ok(bytes)
+    });
+    match result {
+        Ok(bytes) => Ok(Some(bytes)),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+        Err(e) => Err(e.into()),
+    }
+}
@@ -0,0 +1,361 @@
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+
+use crate::error::AppError;
+
+const CONFIG_FILE: &str = "config.json";
+
+/// Default export location, matching revu's historical behaviour.
+pub const DEFAULT_EXPORT_PATH: &str = "~/.revu/{repo_name}-{epoch}.{ext}";
+
+/// Settings read from `~/.revu/config.json`, overridden per repository by `<repo>/.revu/config.json`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct Config {
+    pub export: ExportConfig,
+    /// Rewrites host paths into the paths an agent sees, e.g. inside a devcontainer. Only read
+    /// from the global config.
+    pub path_mappings: Vec<PathMapping>,
+    pub watch: WatchConfig,
+    pub diff: DiffConfig,
+}
+
+/// Limits above which a file's diff is sent as hunk summaries and collapsed in the UI until
+/// requested, so a huge generated file can't hang the webview.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct DiffConfig {
+    /// Lines across all of a file's hunks.
+    pub large_file_lines: usize,
+    /// Bytes of line content across all of a file's hunks.
+    pub large_file_bytes: usize,
+}
+
+impl Default for DiffConfig {
+    fn default() -> Self {
+        Self {
+            large_file_lines: 5000,
+            large_file_bytes: 512 * 1024,
+        }
+    }
+}
+
+/// How the open repository is watched for changes made outside revu.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct WatchConfig {
+    pub enabled: bool,
+    /// Changes are batched until nothing has changed for this long.
+    pub debounce_ms: u64,
+    /// Quiet period after the last change before the repository counts as settled, i.e. the
+    /// agent has stopped writing.
+    pub settle_ms: u64,
+}
+
+impl Default for WatchConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            debounce_ms: 200,
+            settle_ms: 3000,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PathMapping {
+    /// Path prefix on the machine running revu, e.g. `/home/me/src/project`.
+    pub host: String,
+    /// The same location as the agent sees it, e.g. `/workspace`.
+    pub agent: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct ExportConfig {
+    /// Where a rendered review is delivered; every destination is attempted in order.
+    pub destinations: Vec<Destination>,
+    /// Write absolute (agent-mapped) file paths in exports instead of repository-relative ones.
+    pub absolute_paths: bool,
+    /// Destinations a repository's own config asked for but isn't trusted to set, reported
+    /// as failed deliveries instead of being run.
+    #[serde(skip)]
+    pub refused: Vec<Destination>,
+    /// Set when `destinations` came from a repository's config, so file destinations are
+    /// checked again as they are written, in case a symlink has appeared since.
+    #[serde(skip)]
+    pub confined: bool,
+}
+
+impl Default for ExportConfig {
+    fn default() -> Self {
+        Self {
+            destinations: vec![Destination::File {
+                path: DEFAULT_EXPORT_PATH.to_string(),
+            }],
+            absolute_paths: false,
+            refused: Vec::new(),
+            confined: false,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(
+    tag = "type",
+    rename_all = "camelCase",
+    rename_all_fields = "camelCase"
+)]
+pub enum Destination {
+    /// A file path template. Supports `~`, `{home}`, `{repo}`, `{repo_name}`, `{epoch}` and
+    /// `{ext}`; relative paths are resolved against the repository root.
+    File { path: String },
+    /// The process's standard output, for `revu --wait`.
+    Stdout,
+    /// A named pipe (FIFO) that some other process is reading.
+    Pipe { path: String },
+    /// A tmux pane, typed in with `tmux send-keys`.
+    Tmux {
+        target: String,
+        #[serde(default)]
+        send: TmuxPayload,
+        #[serde(default)]
+        press_enter: bool,
+    },
+    /// A shell command that receives the review on stdin.
+    Command { command: String },
+}
+
+impl Destination {
+    /// Whether the destination runs a command or writes into another process, which only
+    /// the global config may ask for: a repository's config arrives with the code under
+    /// review, so anyone who can commit to it could otherwise run commands on export.
+    pub fn needs_trust(&self) -> bool {
+        matches!(
+            self,
+            Destination::Pipe { .. } | Destination::Tmux { .. } | Destination::Command { .. }
+        )
+    }
+
+    /// Whether a repository's config may ask for the destination: anything but a file that
+    /// would land outside the repository or `~/.revu`, such as `~/.bashrc`.
+    fn allowed_in_repo(&self, repo_root: &Path) -> bool {
+        match self {
+            Destination::File { path } => {
+                is_confined(&resolve_template(path, repo_root), repo_root)
+            }
+            other => !other.needs_trust(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum TmuxPayload {
+    /// The exported file's path as an `@path` reference (falls back to content without a file).
+    #[default]
+    Path,
+    /// The full review text.
+    Content,
+}
+
+/// A config file on disk, where each section is optional so it can override another.
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+struct ConfigLayer {
+    export: Option<ExportLayer>,
+    path_mappings: Option<Vec<PathMapping>>,
+    watch: Option<WatchConfig>,
+    diff: Option<DiffConfig>,
+}
+
+/// The export section of a config file, where each field is optional so a repository can
+/// override one without dropping the global config's others.
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+struct ExportLayer {
+    destinations: Option<Vec<Destination>>,
+    absolute_paths: Option<bool>,
+}
+
+impl Config {
+    /// Loads the global config, then applies the repository's own config on top, field by
+    /// field. Path mappings, pipe, tmux and command destinations, and files outside the
+    /// repository and `~/.revu`, are only taken from the global config.
+    pub fn load(repo_root: Option<&Path>) -> Result<Self, AppError> {
+        let mut config = Config::default();
+
+        for path in Self::layer_paths(repo_root)? {
+            let Some(layer) = read_layer(&path)? else {
+                continue;
+            };
+            // Only the second layer's path is inside the repository
+            let from_repo = repo_root.filter(|root| path.starts_with(root));
+            if let Some(export) = layer.export {
+                if let Some(destinations) = export.destinations {
+                    match from_repo {
+                        Some(root) => {
+                            let (allowed, refused) = destinations
+                                .into_iter()
+                                .partition(|d| d.allowed_in_repo(root));
+                            config.export.destinations = allowed;
+                            config.export.refused = refused;
+                            config.export.confined = true;
+                        }
+                        None => config.export.destinations = destinations,
+                    }
+                }
+                if let Some(absolute_paths) = export.absolute_paths {
+                    config.export.absolute_paths = absolute_paths;
+                }
+            }
+            // They decide which files the agent is told to edit, so only the user sets them
+            if let (Some(mappings), None) = (layer.path_mappings, from_repo) {
+                config.path_mappings = mappings;
+            }
+            if let Some(watch) = layer.watch {
+                config.watch = watch;
+            }
+            if let Some(diff) = layer.diff {
+                config.diff = diff;
+            }
+        }
+
+        Ok(config)
+    }
+
+    /// The config files `load` reads, lowest precedence first.
+    pub fn layer_paths(repo_root: Option<&Path>) -> Result<Vec<PathBuf>, AppError> {
+        let mut layers = vec![revu_dir()?.join(CONFIG_FILE)];
+        if let Some(root) = repo_root {
+            layers.push(root.join(".revu").join(CONFIG_FILE));
+        }
+        Ok(layers)
+    }
+
+    /// Translates a host path into the agent's view of it.
+    pub fn map_path(&self, path: &Path) -> String {
+        map_path(&self.path_mappings, path)
+    }
+}
+
+/// Replaces the longest host prefix matching `path` (by whole components) with its agent prefix.
+pub fn map_path(mappings: &[PathMapping], path: &Path) -> String {
+    let best = mappings
+        .iter()
+        .filter_map(|m| {
+            path.strip_prefix(&m.host)
+                .ok()
+                .map(|rest| (Path::new(&m.host).components().count(), m, rest))
+        })
+        .max_by_key(|(depth, _, _)| *depth);
+
+    match best {
+        Some((_, mapping, rest)) if rest.as_os_str().is_empty() => mapping.agent.clone(),
+        Some((_, mapping, rest)) => Path::new(&mapping.agent)
+            .join(rest)
+            .to_string_lossy()
+            .to_string(),
+        None => path.to_string_lossy().to_string(),
+    }
+}
+
+/// Expands the parts of a file destination's template that decide where it lands (`~`,
+/// `{home}` and `{repo}`), resolving a relative path against the repository root. The other
+/// placeholders only ever fill in a file name.
+fn resolve_template(template: &str, repo_root: &Path) -> PathBuf {
+    let home = home_dir().map(|home| home.to_string_lossy().to_string());
+    let home = home.as_deref().unwrap_or("~");
+    let mut expanded = template
+        .replace("{home}", home)
+        .replace("{repo}", &repo_root.to_string_lossy());
+    if expanded == "~" || expanded.starts_with("~/") {
+        expanded = format!("{}{}", home, &expanded[1..]);
+    }
+    repo_root.join(expanded)
+}
+
+/// Whether `path` is inside the repository or `~/.revu` once `..` and any symlinks on the
+/// way are resolved, for a file a repository's config asked to write.
+pub fn is_confined(path: &Path, repo_root: &Path) -> bool {
+    let Ok(revu) = revu_dir() else {
+        return false;
+    };
+    let roots = [resolve(repo_root), resolve(&revu)];
+    let path = resolve(path);
+    roots.iter().any(|root| path.starts_with(root))
+}
+
+/// Resolves `..` and symlinks in the part of an absolute path that exists on disk, the way
+/// the OS will when the file is written, then applies any `..` in the part that doesn't
+/// exist yet (which can't hold a symlink).
+fn resolve(path: &Path) -> PathBuf {
+    resolve_links(path, MAX_LINKS)
+}
+
+/// How many dangling symlinks `resolve` follows before giving up on a path, like the OS's
+/// own limit on symlink loops.
+const MAX_LINKS: u32 = 40;
+
+fn resolve_links(path: &Path, links: u32) -> PathBuf {
+    let mut missing = Vec::new();
+    let mut existing = path;
+    let mut resolved = loop {
+        if let Ok(resolved) = fs::canonicalize(existing) {
+            break resolved;
+        }
+        // A symlink to something that doesn't exist yet would be written through too
+        if let Ok(target) = fs::read_link(existing) {
+            if links == 0 {
+                return PathBuf::new();
+            }
+            let parent = existing.parent().unwrap_or(Path::new("/"));
+            break resolve_links(&parent.join(target), links - 1);
+        }
+        match (existing.parent(), existing.components().next_back()) {
+            (Some(parent), Some(last)) => {
+                missing.push(last);
+                existing = parent;
+            }
+            _ => break PathBuf::new(),
+        }
+    };
+    for component in missing.into_iter().rev() {
+        match component {
+            Component::ParentDir => {
+                resolved.pop();
+            }
+            Component::CurDir => {}
+            other => resolved.push(other),
+        }
+    }
+    resolved
+}
+
+fn read_layer(path: &Path) -> Result<Option<ConfigLayer>, AppError> {
+    let content = match fs::read_to_string(path) {
+        Ok(content) => content,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+        Err(e) => return Err(e.into()),
+    };
+    serde_json::from_str(&content)
+        .map(Some)
+        .map_err(|e| AppError::Custom(format!("Invalid config {}: {}", path.display(), e)))
+}
+
+pub fn home_dir() -> Result<PathBuf, AppError> {
+    let home = std::env::var("HOME").map_err(|_| {
+        std::io::Error::new(
+            std::io::ErrorKind::NotFound,
+            "HOME environment variable not set",
+        )
+    })?;
+    Ok(PathBuf::from(home))
+}
+
+/// revu's own directory, `~/.revu`.
+pub fn revu_dir() -> Result<PathBuf, AppError> {
+    Ok(home_dir()?.join(".revu"))
+}
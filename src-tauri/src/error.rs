@@ -11,6 +11,10 @@ pub enum AppError {
     #[error("IO error: {0}")]
     Io(#[from] std::io::Error),
 
+    /// The request was superseded or cancelled before it finished.
+    #[error("Request cancelled")]
+    Cancelled,
+
     #[error("{0}")]
     Custom(String),
 }
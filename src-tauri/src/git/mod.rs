@@ -1,5 +1,12 @@
+pub mod moved;
+pub mod options;
+pub mod parse;
 pub mod repository;
+pub mod text;
 pub mod types;
 
-pub use repository::GitRepository;
+pub use moved::detect_moves;
+pub use options::{DiffAlgorithm, DiffSettings};
+pub use parse::{diff_decoded, normalize_patch, parse_diff, parse_patch};
+pub use repository::{detect_language, GitRepository};
 pub use types::*;
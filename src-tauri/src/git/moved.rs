@@ -0,0 +1,305 @@
+use std::collections::HashMap;
+
+use super::types::*;
+
+/// A block must have at least this many alphanumeric characters to count as moved, so that
+/// braces and blank lines aren't matched all over the diff. Git uses the same threshold.
+const MIN_ALNUM: usize = 20;
+
+/// Lines starting a block are only compared against this many identical deleted lines.
+const MAX_CANDIDATES: usize = 64;
+
+/// A changed line, located in the files being diffed.
+struct Changed<'a> {
+    file: usize,
+    hunk: usize,
+    line: usize,
+    /// Consecutive lines of the same type in a hunk share a run, which a block can't leave.
+    run: usize,
+    /// The content without indentation, trailing whitespace or line ending.
+    key: &'a str,
+}
+
+impl Changed<'_> {
+    fn at(&self) -> (usize, usize, usize) {
+        (self.file, self.hunk, self.line)
+    }
+}
+
+/// Marks blocks of deleted lines that reappear as added lines elsewhere in `files`, the way
+/// `git diff --color-moved` does. Indentation is ignored, so code moved into or out of a
+/// nested scope is found, and a block may have lines that changed as it moved, as long as
+/// no two changed lines are adjacent. Lines that are deleted and re-added in the same hunk
+/// are left alone, since that is a re-indentation rather than a move.
+pub fn detect_moves(files: &mut [FileDiff]) {
+    for (at, moved) in find_moves(files) {
+        let (file, hunk, line) = at;
+        files[file].hunks[hunk].lines[line].moved = Some(moved);
+    }
+}
+
+/// Where each moved line is, by file, hunk and line index, and what to mark it with.
+fn find_moves(files: &[FileDiff]) -> Vec<((usize, usize, usize), LineMove)> {
+    let (deleted, added) = changed_lines(files);
+
+    let mut by_key: HashMap<&str, Vec<usize>> = HashMap::new();
+    for (index, line) in deleted.iter().enumerate() {
+        if !line.key.is_empty() {
+            by_key.entry(line.key).or_default().push(index);
+        }
+    }
+
+    let mut used = vec![false; deleted.len()];
+    let mut moves = Vec::new();
+    let mut start = 0;
+    while start < added.len() {
+        let candidates = by_key.get(added[start].key).map_or(&[][..], Vec::as_slice);
+        let best = candidates
+            .iter()
+            .filter(|&&del| !used[del] && !same_hunk(&added[start], &deleted[del]))
+            .take(MAX_CANDIDATES)
+            .map(|&del| extend_block(&added, &deleted, &used, start, del))
+            .filter(|block| block.alnum >= MIN_ALNUM)
+            .max_by_key(|block| (block.len, std::cmp::Reverse(block.edited)));
+
+        let Some(block) = best else {
+            start += 1;
+            continue;
+        };
+        for offset in 0..block.len {
+            let (add, del) = (&added[start + offset], &deleted[block.deleted + offset]);
+            used[block.deleted + offset] = true;
+            let target = |to: &Changed, line_no: Option<u32>| LineMove {
+                path: files[to.file].path.clone(),
+                line: line_no.unwrap_or_default(),
+                edited: block.edited,
+            };
+            moves.push((add.at(), target(del, line_at(files, del).old_line_no)));
+            moves.push((del.at(), target(add, line_at(files, add).new_line_no)));
+        }
+        start += block.len;
+    }
+    moves
+}
+
+struct Block {
+    deleted: usize,
+    len: usize,
+    alnum: usize,
+    edited: bool,
+}
+
+/// Follows added lines from `add` alongside deleted lines from `del` while they match. A
+/// mismatched pair is taken as an edit when the pair after it matches again.
+fn extend_block(
+    added: &[Changed],
+    deleted: &[Changed],
+    used: &[bool],
+    add: usize,
+    del: usize,
+) -> Block {
+    let in_block = |offset: usize| {
+        let (a, d) = (add + offset, del + offset);
+        a < added.len()
+            && d < deleted.len()
+            && added[a].run == added[add].run
+            && deleted[d].run == deleted[del].run
+            && !used[d]
+    };
+    let matches =
+        |offset: usize| in_block(offset) && added[add + offset].key == deleted[del + offset].key;
+
+    let mut block = Block {
+        deleted: del,
+        len: 0,
+        alnum: 0,
+        edited: false,
+    };
+    loop {
+        if matches(block.len) {
+            block.alnum += alnum_count(added[add + block.len].key);
+        } else if in_block(block.len) && matches(block.len + 1) {
+            block.edited = true;
+        } else {
+            return block;
+        }
+        block.len += 1;
+    }
+}
+
+/// Every deleted and added line of `files`, in diff order.
+fn changed_lines(files: &[FileDiff]) -> (Vec<Changed<'_>>, Vec<Changed<'_>>) {
+    let (mut deleted, mut added) = (Vec::new(), Vec::new());
+    let mut run = 0;
+    for (file_index, file) in files.iter().enumerate() {
+        for (hunk_index, hunk) in file.hunks.iter().enumerate() {
+            let mut previous = None;
+            for (line_index, line) in hunk.lines.iter().enumerate() {
+                if previous.as_ref() != Some(&line.line_type) {
+                    run += 1;
+                    previous = Some(line.line_type.clone());
+                }
+                let side = match line.line_type {
+                    LineType::Deletion => &mut deleted,
+                    LineType::Addition => &mut added,
+                    _ => continue,
+                };
+                side.push(Changed {
+                    file: file_index,
+                    hunk: hunk_index,
+                    line: line_index,
+                    run,
+                    key: line.content.trim(),
+                });
+            }
+        }
+    }
+    (deleted, added)
+}
+
+fn same_hunk(a: &Changed, b: &Changed) -> bool {
+    a.file == b.file && a.hunk == b.hunk
+}
+
+fn line_at<'a>(files: &'a [FileDiff], at: &Changed) -> &'a DiffLine {
+    &files[at.file].hunks[at.hunk].lines[at.line]
+}
+
+fn alnum_count(text: &str) -> usize {
+    text.chars().filter(|c| c.is_alphanumeric()).count()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn line(line_type: LineType, content: &str, old: Option<u32>, new: Option<u32>) -> DiffLine {
+        DiffLine {
+            line_type,
+            content: format!("{}\n", content),
+            old_line_no: old,
+            new_line_no: new,
+            no_newline: false,
+            moved: None,
+        }
+    }
+
+    fn hunk(lines: Vec<DiffLine>) -> DiffHunk {
+        DiffHunk {
+            header: "@@".to_string(),
+            old_start: 1,
+            old_lines: 0,
+            new_start: 1,
+            new_lines: 0,
+            lines,
+            omitted_lines: 0,
+        }
+    }
+
+    fn file(path: &str, hunks: Vec<DiffHunk>) -> FileDiff {
+        FileDiff {
+            path: path.to_string(),
+            old_path: None,
+            status: FileStatus::Modified,
+            hunks,
+            is_binary: false,
+            language: None,
+            is_large: false,
+            encoding: None,
+            has_bom: false,
+            old_mode: None,
+            new_mode: None,
+            old_link_target: None,
+            new_link_target: None,
+            line_endings_only: false,
+        }
+    }
+
+    fn deleted(lines: &[&str], from: u32) -> DiffHunk {
+        let lines = (from..).zip(lines);
+        hunk(
+            lines
+                .map(|(n, l)| line(LineType::Deletion, l, Some(n), None))
+                .collect(),
+        )
+    }
+
+    fn added(lines: &[&str], from: u32) -> DiffHunk {
+        let lines = (from..).zip(lines);
+        hunk(
+            lines
+                .map(|(n, l)| line(LineType::Addition, l, None, Some(n)))
+                .collect(),
+        )
+    }
+
+    fn moves(file: &FileDiff) -> Vec<Option<&LineMove>> {
+        let lines = file.hunks.iter().flat_map(|hunk| &hunk.lines);
+        lines.map(|line| line.moved.as_ref()).collect()
+    }
+
+    const BLOCK: [&str; 3] = [
+        "fn parse_config(path: &Path) -> Config {",
+        "    let text = read_to_string(path);",
+        "}",
+    ];
+
+    #[test]
+    fn marks_exact_move_across_files() {
+        let indented: Vec<String> = BLOCK.iter().map(|l| format!("    {}", l)).collect();
+        let indented: Vec<&str> = indented.iter().map(String::as_str).collect();
+        let mut files = vec![
+            file("a.rs", vec![deleted(&BLOCK, 10)]),
+            file("b.rs", vec![added(&indented, 4)]),
+        ];
+        detect_moves(&mut files);
+
+        let from = moves(&files[0]);
+        assert!(from
+            .iter()
+            .all(|m| m.is_some_and(|m| m.path == "b.rs" && !m.edited)));
+        assert_eq!(from[0].unwrap().line, 4);
+        let to = moves(&files[1]);
+        assert!(to
+            .iter()
+            .all(|m| m.is_some_and(|m| m.path == "a.rs" && !m.edited)));
+        assert_eq!(to[2].unwrap().line, 12);
+    }
+
+    #[test]
+    fn marks_edited_move() {
+        let edited = [BLOCK[0], "    let text = read(path);", BLOCK[2]];
+        let mut files = vec![
+            file("a.rs", vec![deleted(&BLOCK, 1)]),
+            file("b.rs", vec![added(&edited, 1)]),
+        ];
+        detect_moves(&mut files);
+
+        assert!(moves(&files[1]).iter().all(|m| m.is_some_and(|m| m.edited)));
+    }
+
+    #[test]
+    fn leaves_reindent_in_same_hunk() {
+        let mut lines = deleted(&BLOCK, 1).lines;
+        let indented: Vec<String> = BLOCK.iter().map(|l| format!("\t{}", l)).collect();
+        let indented: Vec<&str> = indented.iter().map(String::as_str).collect();
+        lines.extend(added(&indented, 1).lines);
+        let mut files = vec![file("a.rs", vec![hunk(lines)])];
+        detect_moves(&mut files);
+
+        assert!(moves(&files[0]).iter().all(Option::is_none));
+    }
+
+    #[test]
+    fn ignores_blocks_with_little_text() {
+        let short = ["}", "x += 1;", "}"];
+        let mut files = vec![
+            file("a.rs", vec![deleted(&short, 1)]),
+            file("b.rs", vec![added(&short, 1)]),
+        ];
+        detect_moves(&mut files);
+
+        assert!(moves(&files[0]).iter().all(Option::is_none));
+        assert!(moves(&files[1]).iter().all(Option::is_none));
+    }
+}
@@ -0,0 +1,122 @@
+use git2::{Config, DiffOptions};
+use serde::{Deserialize, Serialize};
+
+/// Context lines when neither the request nor `diff.context` says.
+const DEFAULT_CONTEXT_LINES: u32 = 3;
+
+/// How a diff is computed, as requested by the UI. Anything left unset is taken from the
+/// user's git config (`diff.context`, `diff.algorithm`, `diff.indentHeuristic`,
+/// `diff.colorMoved`), then from git's own defaults.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct DiffSettings {
+    pub context_lines: Option<u32>,
+    pub algorithm: Option<DiffAlgorithm>,
+    /// Shifts hunk boundaries to follow indentation, which git does by default.
+    pub indent_heuristic: Option<bool>,
+    /// Ignore all whitespace (`git diff -w`).
+    pub ignore_whitespace: bool,
+    /// Ignore changes in the amount of whitespace (`-b`).
+    pub ignore_whitespace_change: bool,
+    /// Ignore whitespace at the end of lines (`--ignore-space-at-eol`).
+    pub ignore_whitespace_eol: bool,
+    /// Ignore changes that only add or remove blank lines (`--ignore-blank-lines`).
+    pub ignore_blank_lines: bool,
+    /// Mark code moved between or within files, see [`super::detect_moves`]. Off by default,
+    /// as in git.
+    pub detect_moves: Option<bool>,
+}
+
+/// A diff algorithm, named as in git's `diff.algorithm`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DiffAlgorithm {
+    #[serde(alias = "default")]
+    Myers,
+    Minimal,
+    Patience,
+    /// libgit2 has no histogram diff, so this runs patience, which histogram extends and
+    /// which gives the same hunks for most changes.
+    Histogram,
+}
+
+impl DiffAlgorithm {
+    fn from_name(name: &str) -> Option<Self> {
+        match name.trim().to_ascii_lowercase().as_str() {
+            "myers" | "default" => Some(Self::Myers),
+            "minimal" => Some(Self::Minimal),
+            "patience" => Some(Self::Patience),
+            "histogram" => Some(Self::Histogram),
+            _ => None,
+        }
+    }
+}
+
+impl DiffSettings {
+    /// Only sets the context lines, leaving everything else to git config.
+    pub fn with_context(context_lines: u32) -> Self {
+        Self {
+            context_lines: Some(context_lines),
+            ..Self::default()
+        }
+    }
+
+    /// Whether to look for moved code, which git config turns on with any `diff.colorMoved`
+    /// mode other than `no`.
+    pub fn detects_moves(&self, config: Option<&Config>) -> bool {
+        self.detect_moves
+            .or_else(|| {
+                let mode = config?.get_string("diff.colorMoved").ok()?;
+                let off = ["no", "false", "off", "0", ""];
+                Some(!off.contains(&mode.trim().to_ascii_lowercase().as_str()))
+            })
+            .unwrap_or(false)
+    }
+
+    /// These settings with everything git config decides filled in from `config`, or git's
+    /// defaults, so the UI can show what a diff will actually use.
+    pub fn resolve(&self, config: Option<&Config>) -> DiffSettings {
+        let context_lines = self
+            .context_lines
+            .or_else(|| {
+                let context = config?.get_i32("diff.context").ok()?;
+                u32::try_from(context).ok()
+            })
+            .unwrap_or(DEFAULT_CONTEXT_LINES);
+        let algorithm = self
+            .algorithm
+            .or_else(|| DiffAlgorithm::from_name(&config?.get_string("diff.algorithm").ok()?))
+            .unwrap_or(DiffAlgorithm::Myers);
+        let indent_heuristic = self
+            .indent_heuristic
+            .or_else(|| config?.get_bool("diff.indentHeuristic").ok())
+            .unwrap_or(true);
+        DiffSettings {
+            context_lines: Some(context_lines),
+            algorithm: Some(algorithm),
+            indent_heuristic: Some(indent_heuristic),
+            detect_moves: Some(self.detects_moves(config)),
+            ..self.clone()
+        }
+    }
+
+    /// Builds libgit2's options, filling in what isn't set from `config`.
+    pub fn to_options(&self, config: Option<&Config>) -> DiffOptions {
+        let resolved = self.resolve(config);
+        let algorithm = resolved.algorithm.unwrap_or(DiffAlgorithm::Myers);
+
+        let mut opts = DiffOptions::new();
+        opts.context_lines(resolved.context_lines.unwrap_or(DEFAULT_CONTEXT_LINES))
+            .minimal(algorithm == DiffAlgorithm::Minimal)
+            .patience(matches!(
+                algorithm,
+                DiffAlgorithm::Patience | DiffAlgorithm::Histogram
+            ))
+            .indent_heuristic(resolved.indent_heuristic.unwrap_or(true))
+            .ignore_whitespace(self.ignore_whitespace)
+            .ignore_whitespace_change(self.ignore_whitespace_change)
+            .ignore_whitespace_eol(self.ignore_whitespace_eol)
+            .ignore_blank_lines(self.ignore_blank_lines);
+        opts
+    }
+}
@@ -0,0 +1,343 @@
+use encoding_rs::{Encoding, UTF_8};
+use git2::{Delta, Diff, DiffDelta, DiffFile, DiffOptions, FileMode, Patch};
+use std::borrow::Cow;
+use std::path::Path;
+
+use super::repository::detect_language;
+use super::text;
+use super::types::*;
+use crate::error::AppError;
+
+/// Parses a unified diff (e.g. `git diff` output or a `.patch` file) into the same per-file
+/// structure as the repository's own diffs. Needs no repository, so it also serves patches
+/// reviewed on their own.
+pub fn parse_patch(patch: &str) -> Result<Vec<FileDiff>, AppError> {
+    let diff = Diff::from_buffer(normalize_patch(patch).as_bytes())?;
+    parse_diff_files(&diff)
+}
+
+/// libgit2 only reads git-style patches, so give a plain `diff -u` the `diff --git` header
+/// it expects before each file. Git patches are returned untouched.
+pub fn normalize_patch(patch: &str) -> Cow<'_, str> {
+    if patch.lines().any(|line| line.starts_with("diff --git ")) {
+        return Cow::Borrowed(patch);
+    }
+
+    let mut out = String::with_capacity(patch.len());
+    let mut lines = patch.split_inclusive('\n').peekable();
+    while let Some(line) = lines.next() {
+        let next = lines.peek().copied();
+        let (Some(old), Some(new)) = (
+            line.strip_prefix("--- "),
+            next.and_then(|l| l.strip_prefix("+++ ")),
+        ) else {
+            out.push_str(line);
+            continue;
+        };
+        lines.next();
+
+        // A plain diff compares two names for one file (e.g. `x.orig` and `x`); keep the new one
+        let (old, new) = plain_paths(old, new);
+        let path = new.or(old).unwrap_or_default();
+        out.push_str(&format!("diff --git a/{} b/{}\n", path, path));
+        match (old, new) {
+            (None, _) => out.push_str(&format!(
+                "new file mode 100644\n--- /dev/null\n+++ b/{}\n",
+                path
+            )),
+            (_, None) => out.push_str(&format!(
+                "deleted file mode 100644\n--- a/{}\n+++ /dev/null\n",
+                path
+            )),
+            _ => out.push_str(&format!("--- a/{}\n+++ b/{}\n", path, path)),
+        }
+    }
+    Cow::Owned(out)
+}
+
+/// Reads the file names from a plain diff's `---`/`+++` lines, dropping timestamps and a
+/// leading directory the two sides differ by (`diff -ru old/ new/`). `None` is `/dev/null`.
+fn plain_paths<'a>(old: &'a str, new: &'a str) -> (Option<&'a str>, Option<&'a str>) {
+    let name = |field: &'a str| {
+        let name = field.trim_end_matches(['\r', '\n']);
+        let name = name.split('\t').next().unwrap_or(name);
+        (name != "/dev/null").then_some(name)
+    };
+    let (old, new) = (name(old), name(new));
+
+    match (old, new) {
+        (Some(old), Some(new)) => {
+            let strip = |path: &'a str| path.split_once('/').map(|(_, rest)| rest);
+            match (strip(old), strip(new)) {
+                (Some(old_rest), Some(new_rest)) if old_rest == new_rest => {
+                    (Some(old_rest), Some(new_rest))
+                }
+                _ => (Some(old), Some(new)),
+            }
+        }
+        // Added and deleted files only lose the conventional a/ and b/ prefixes
+        (Some(old), None) => (Some(old.strip_prefix("a/").unwrap_or(old)), None),
+        (None, Some(new)) => (None, Some(new.strip_prefix("b/").unwrap_or(new))),
+        (None, None) => (None, None),
+    }
+}
+
+/// Builds every file's `FileDiff` out of a libgit2 diff in one walk over its deltas.
+pub fn parse_diff_files(diff: &Diff) -> Result<Vec<FileDiff>, AppError> {
+    (0..diff.deltas().len())
+        .map(|idx| parse_delta(diff, idx))
+        .collect()
+}
+
+/// Collects one file's delta and hunks out of a libgit2 diff. A file the diff doesn't touch
+/// comes back as an unchanged modification with no hunks.
+pub fn parse_diff(diff: &Diff, file_path: &str) -> Result<FileDiff, AppError> {
+    match diff
+        .deltas()
+        .position(|delta| delta_path(&delta) == file_path)
+    {
+        Some(idx) => parse_delta(diff, idx),
+        None => Ok(FileDiff {
+            path: file_path.to_string(),
+            old_path: None,
+            status: FileStatus::Modified,
+            hunks: vec![],
+            is_binary: false,
+            language: detect_language(file_path),
+            is_large: false,
+            encoding: None,
+            has_bom: false,
+            old_mode: None,
+            new_mode: None,
+            old_link_target: None,
+            new_link_target: None,
+            line_endings_only: false,
+        }),
+    }
+}
+
+/// The path a delta is shown under: the new path, or the old one for a deletion.
+pub fn delta_path(delta: &DiffDelta) -> String {
+    delta
+        .new_file()
+        .path()
+        .or_else(|| delta.old_file().path())
+        .map(|p| p.to_string_lossy().to_string())
+        .unwrap_or_default()
+}
+
+/// Parses the `idx`th file of a diff.
+pub fn parse_delta(diff: &Diff, idx: usize) -> Result<FileDiff, AppError> {
+    // Generating the patch loads the file contents, which is also when libgit2 finds out
+    // whether they are binary, so read the delta back from the patch where there is one
+    let patch = Patch::from_diff(diff, idx)?;
+    let delta = match &patch {
+        Some(patch) => patch.delta(),
+        None => diff
+            .get_delta(idx)
+            .ok_or_else(|| AppError::Custom(format!("Missing diff delta {}", idx)))?,
+    };
+
+    let path = delta_path(&delta);
+    let mut old_path = None;
+    let status = match delta.status() {
+        Delta::Added => FileStatus::Added,
+        Delta::Deleted => FileStatus::Deleted,
+        Delta::Modified => FileStatus::Modified,
+        Delta::Renamed => {
+            old_path = delta
+                .old_file()
+                .path()
+                .map(|p| p.to_string_lossy().to_string());
+            FileStatus::Renamed
+        }
+        Delta::Copied => FileStatus::Copied,
+        _ => FileStatus::Modified,
+    };
+    let is_binary = delta.flags().is_binary();
+    let old_mode = file_mode(&delta.old_file());
+    let new_mode = file_mode(&delta.new_file());
+
+    let (hunks, plain_utf8) = match patch {
+        Some(patch) if !is_binary => parse_hunks(&patch)?,
+        _ => (vec![], !is_binary),
+    };
+
+    let is_link = |mode: Option<u32>| mode == Some(u32::from(FileMode::Link));
+    let old_link_target = is_link(old_mode)
+        .then(|| link_target(&hunks, true))
+        .flatten();
+    let new_link_target = is_link(new_mode)
+        .then(|| link_target(&hunks, false))
+        .flatten();
+    let line_endings_only = line_endings_only(&hunks);
+
+    Ok(FileDiff {
+        language: detect_language(&path),
+        path,
+        old_path,
+        status,
+        hunks,
+        is_binary,
+        is_large: false,
+        encoding: plain_utf8.then(|| UTF_8.name().to_string()),
+        has_bom: false,
+        old_mode,
+        new_mode,
+        old_link_target,
+        new_link_target,
+        line_endings_only,
+    })
+}
+
+/// A side's mode, if the file exists there and the diff records it.
+fn file_mode(file: &DiffFile) -> Option<u32> {
+    Some(u32::from(file.mode())).filter(|&mode| mode != 0)
+}
+
+/// A symlink's target, which git diffs as the link's only line.
+fn link_target(hunks: &[DiffHunk], old: bool) -> Option<String> {
+    hunks
+        .iter()
+        .flat_map(|hunk| &hunk.lines)
+        .find(|line| match line.line_type {
+            LineType::Deletion => old,
+            LineType::Addition => !old,
+            _ => true,
+        })
+        .map(|line| line.content.trim_end_matches('\n').to_string())
+}
+
+/// Whether the file has changes and every one of them only changes line endings: each
+/// hunk's removed lines equal its added lines once their line endings are dropped.
+fn line_endings_only(hunks: &[DiffHunk]) -> bool {
+    fn side(hunk: &DiffHunk, line_type: LineType) -> impl Iterator<Item = &str> {
+        hunk.lines
+            .iter()
+            .filter(move |line| line.line_type == line_type)
+            .map(|line| line.content.trim_end_matches(['\r', '\n']))
+    }
+    let changed = hunks
+        .iter()
+        .flat_map(|hunk| &hunk.lines)
+        .any(|line| line.line_type != LineType::Context);
+    changed
+        && hunks
+            .iter()
+            .all(|hunk| side(hunk, LineType::Deletion).eq(side(hunk, LineType::Addition)))
+}
+
+/// Also reports whether every line was UTF-8 without a byte order mark, i.e. whether the
+/// lines came out as the file's text.
+fn parse_hunks(patch: &Patch) -> Result<(Vec<DiffHunk>, bool), AppError> {
+    let mut plain_utf8 = true;
+    let mut hunks = Vec::with_capacity(patch.num_hunks());
+    for hunk_idx in 0..patch.num_hunks() {
+        let (hunk, line_count) = patch.hunk(hunk_idx)?;
+        let mut lines: Vec<DiffLine> = Vec::with_capacity(line_count);
+        for line_idx in 0..line_count {
+            let line = patch.line_in_hunk(hunk_idx, line_idx)?;
+            let (line_type, old_line_no, new_line_no) = match line.origin() {
+                '+' => (LineType::Addition, None, line.new_lineno()),
+                '-' => (LineType::Deletion, line.old_lineno(), None),
+                ' ' => (LineType::Context, line.old_lineno(), line.new_lineno()),
+                // `\ No newline at end of file`, which follows the line it applies to
+                '=' | '>' | '<' => {
+                    if let Some(last) = lines.last_mut() {
+                        last.no_newline = true;
+                    }
+                    continue;
+                }
+                _ => continue,
+            };
+            let first_line = old_line_no == Some(1) || new_line_no == Some(1);
+            plain_utf8 &= std::str::from_utf8(line.content()).is_ok()
+                && !(first_line && line.content().starts_with(b"\xEF\xBB\xBF"));
+            lines.push(DiffLine {
+                line_type,
+                content: String::from_utf8_lossy(line.content()).to_string(),
+                old_line_no,
+                new_line_no,
+                no_newline: false,
+                moved: None,
+            });
+        }
+
+        if !lines.is_empty() {
+            hunks.push(DiffHunk {
+                header: String::from_utf8_lossy(hunk.header()).trim().to_string(),
+                old_start: hunk.old_start(),
+                old_lines: hunk.old_lines(),
+                new_start: hunk.new_start(),
+                new_lines: hunk.new_lines(),
+                lines,
+                omitted_lines: 0,
+            });
+        }
+    }
+    Ok((hunks, plain_utf8))
+}
+
+/// Diffs two versions of a file as decoded text, for content libgit2 would show as binary
+/// (UTF-16) or as replacement characters (legacy encodings). `None` is a missing side;
+/// each side is decoded with its encoding if given, see [`text::decode`]. Returns `None`
+/// if either side is binary.
+pub fn diff_decoded(
+    path: &str,
+    old: Option<(&[u8], Option<&'static Encoding>)>,
+    new: Option<(&[u8], Option<&'static Encoding>)>,
+    opts: &mut DiffOptions,
+) -> Result<Option<FileDiff>, AppError> {
+    // `None` for a binary side, `Some(None)` for a missing one
+    fn decode_side<'a>(
+        side: Option<(&'a [u8], Option<&'static Encoding>)>,
+    ) -> Option<Option<text::Decoded<'a>>> {
+        match side {
+            Some((bytes, encoding)) => text::decode(bytes, encoding).map(Some),
+            None => Some(None),
+        }
+    }
+    let (Some(old), Some(new)) = (decode_side(old), decode_side(new)) else {
+        return Ok(None);
+    };
+
+    let patch = Patch::from_buffers(
+        old.as_ref().map_or(&b""[..], |d| d.text.as_bytes()),
+        old.is_some().then_some(Path::new(path)),
+        new.as_ref().map_or(&b""[..], |d| d.text.as_bytes()),
+        new.is_some().then_some(Path::new(path)),
+        Some(opts),
+    )?
+    .to_buf()?;
+    let status = match (&old, &new) {
+        (None, _) => FileStatus::Added,
+        (_, None) => FileStatus::Deleted,
+        _ => FileStatus::Modified,
+    };
+    let mut file = parse_patch(&String::from_utf8_lossy(&patch))?
+        .into_iter()
+        .next()
+        .unwrap_or_else(|| FileDiff {
+            path: path.to_string(),
+            old_path: None,
+            status: status.clone(),
+            hunks: vec![],
+            is_binary: false,
+            language: detect_language(path),
+            is_large: false,
+            encoding: None,
+            has_bom: false,
+            old_mode: None,
+            new_mode: None,
+            old_link_target: None,
+            new_link_target: None,
+            line_endings_only: false,
+        });
+    file.status = status;
+
+    // Report the side being shown: the new one, or the old one of a deleted file
+    let shown = new.as_ref().or(old.as_ref());
+    file.encoding = shown.map(|d| d.encoding.name().to_string());
+    file.has_bom = shown.is_some_and(|d| d.bom);
+    Ok(Some(file))
+}
@@ -1,11 +1,23 @@
+use encoding_rs::{Encoding, UTF_8};
 use git2::{
-    Delta, Diff, DiffOptions, IndexAddOption, Repository, ResetType, Signature, StatusOptions,
+    ApplyLocation, ApplyOptions, AttrCheckFlags, Diff, DiffDelta, DiffOptions, IndexAddOption, Oid,
+    Repository, ResetType, Signature, StatusOptions,
 };
+use std::collections::HashSet;
+use std::fs::File;
+use std::io::Read;
 use std::path::Path;
 
+use super::options::DiffSettings;
+use super::parse::{delta_path, diff_decoded, normalize_patch, parse_delta, parse_diff};
+use super::text;
 use super::types::*;
 use crate::error::AppError;
 
+/// The most of an untracked file read to show it as added; a larger one is reported as too
+/// large to show. Directory comparisons use the same limit for files on either side.
+pub(crate) const MAX_UNTRACKED_BYTES: u64 = 16 * 1024 * 1024;
+
 pub struct GitRepository {
     repo: Repository,
 }
@@ -17,6 +29,45 @@ impl GitRepository {
         Ok(Self { repo })
     }
 
+    pub fn workdir(&self) -> Option<&Path> {
+        self.repo.workdir()
+    }
+
+    /// The repository's `.git` directory (per worktree).
+    pub fn git_dir(&self) -> &Path {
+        self.repo.path()
+    }
+
+    /// Whether the path (relative to the working tree) is ignored by `.gitignore` or
+    /// `.git/info/exclude`.
+    pub fn is_ignored(&self, file_path: &str) -> bool {
+        self.repo
+            .status_should_ignore(Path::new(file_path))
+            .unwrap_or(false)
+    }
+
+    /// The index, reread first if another process has changed it since the handle last looked.
+    fn index(&self) -> Result<git2::Index, AppError> {
+        let mut index = self.repo.index()?;
+        index.read(false)?;
+        Ok(index)
+    }
+
+    pub fn branch(&self) -> Option<String> {
+        self.repo
+            .head()
+            .ok()
+            .and_then(|h| h.shorthand().map(String::from))
+    }
+
+    pub fn head_commit_id(&self) -> Option<String> {
+        self.repo
+            .head()
+            .ok()
+            .and_then(|h| h.target())
+            .map(|oid| oid.to_string())
+    }
+
     pub fn get_status(&self) -> Result<RepositoryStatus, AppError> {
         let path = self
             .repo
@@ -24,11 +75,7 @@ impl GitRepository {
             .map(|p| p.to_string_lossy().to_string())
             .unwrap_or_default();
 
-        let branch = self
-            .repo
-            .head()
-            .ok()
-            .and_then(|h| h.shorthand().map(String::from));
+        let branch = self.branch();
 
         let mut opts = StatusOptions::new();
         opts.include_untracked(true)
@@ -123,15 +170,10 @@ impl GitRepository {
         &self,
         file_path: &str,
         staged: bool,
-        context_lines: u32,
-        ignore_whitespace: bool,
+        settings: &DiffSettings,
     ) -> Result<FileDiff, AppError> {
-        let mut diff_opts = DiffOptions::new();
+        let mut diff_opts = self.diff_options(settings);
         diff_opts.pathspec(file_path);
-        diff_opts.context_lines(context_lines);
-        if ignore_whitespace {
-            diff_opts.ignore_whitespace(true);
-        }
 
         let diff = if staged {
             let head = self.repo.head()?.peel_to_tree()?;
@@ -142,7 +184,13 @@ impl GitRepository {
                 .diff_index_to_workdir(None, Some(&mut diff_opts))?
         };
 
-        let mut result = self.parse_diff(&diff, file_path)?;
+        let mut result = match diff
+            .deltas()
+            .position(|delta| delta_path(&delta) == file_path)
+        {
+            Some(idx) => self.parse_file(&diff, idx, !staged, &mut diff_opts)?,
+            None => parse_diff(&diff, file_path)?,
+        };
 
         // Handle new files with empty hunks - read file content and create synthetic hunk
         // For untracked files, parse_diff returns Modified (no delta), so check git status
@@ -156,9 +204,29 @@ impl GitRepository {
         Ok(result)
     }
 
-    pub fn get_combined_diff(&self) -> Result<Vec<FileDiff>, AppError> {
-        let mut diff_opts = DiffOptions::new();
-        diff_opts.context_lines(3);
+    pub fn get_combined_diff(&self, settings: &DiffSettings) -> Result<Vec<FileDiff>, AppError> {
+        let mut diffs = Vec::new();
+        self.walk_combined_diff(
+            settings,
+            |_| Ok(()),
+            |diff| {
+                diffs.push(diff);
+                Ok(())
+            },
+        )?;
+        Ok(diffs)
+    }
+
+    /// Produces the combined diff one file at a time: `on_start` gets every changed path up
+    /// front, then `on_file` each file as soon as it is parsed. Either can stop the walk by
+    /// returning an error.
+    pub fn walk_combined_diff(
+        &self,
+        settings: &DiffSettings,
+        on_start: impl FnOnce(&[String]) -> Result<(), AppError>,
+        mut on_file: impl FnMut(FileDiff) -> Result<(), AppError>,
+    ) -> Result<(), AppError> {
+        let mut diff_opts = self.diff_options(settings);
 
         let head_tree = self.repo.head().ok().and_then(|h| h.peel_to_tree().ok());
 
@@ -170,22 +238,292 @@ impl GitRepository {
             .repo
             .diff_index_to_workdir(None, Some(&mut diff_opts))?;
 
-        let mut diffs = Vec::new();
+        // A file with staged changes shows those; unstaged changes only fill in the rest.
+        // Listing the deltas is cheap; the patches are only generated file by file below
+        let mut seen = HashSet::new();
+        let mut paths = Vec::new();
+        let mut files = Vec::new();
+        for diff in [&staged_diff, &workdir_diff] {
+            for (idx, delta) in diff.deltas().enumerate() {
+                let path = delta_path(&delta);
+                if seen.insert(path.clone()) {
+                    paths.push(path);
+                    files.push((diff, idx));
+                }
+            }
+        }
 
-        for delta in staged_diff.deltas() {
-            let path = delta
-                .new_file()
-                .path()
-                .or_else(|| delta.old_file().path())
-                .map(|p| p.to_string_lossy().to_string())
-                .unwrap_or_default();
+        on_start(&paths)?;
+        for (diff, idx) in files {
+            let workdir = std::ptr::eq(diff, &workdir_diff);
+            on_file(self.parse_file(diff, idx, workdir, &mut diff_opts)?)?;
+        }
+        Ok(())
+    }
 
-            if let Ok(diff) = self.parse_diff(&staged_diff, &path) {
-                diffs.push(diff);
+    /// Parses the `idx`th file of a diff, decoding both sides when libgit2's own patch
+    /// can't be shown as is: the file has a `working-tree-encoding` attribute (libgit2
+    /// compares the raw working tree bytes against the UTF-8 blob), isn't valid UTF-8, or
+    /// looks binary only because it is UTF-16. `workdir` says whether the new side is the
+    /// working tree rather than a blob.
+    fn parse_file(
+        &self,
+        diff: &Diff,
+        idx: usize,
+        workdir: bool,
+        opts: &mut DiffOptions,
+    ) -> Result<FileDiff, AppError> {
+        let parsed = parse_delta(diff, idx)?;
+        let attr = self.working_tree_encoding(&parsed.path);
+        if attr.is_none() && !parsed.is_binary && parsed.encoding.is_some() {
+            return Ok(parsed);
+        }
+        let Some(delta) = diff.get_delta(idx) else {
+            return Ok(parsed);
+        };
+        // Most binary files really are, so only read them whole when one side starts like
+        // UTF-16 text
+        if attr.is_none()
+            && parsed.is_binary
+            && !self.sniffs_utf16(&delta, &parsed.path, workdir)?
+        {
+            return Ok(parsed);
+        }
+
+        let old = self.blob_bytes(delta.old_file().id())?;
+        let new = if workdir {
+            match self.read_workdir(&parsed.path, MAX_UNTRACKED_BYTES + 1)? {
+                Some(bytes) if bytes.len() as u64 > MAX_UNTRACKED_BYTES => return Ok(parsed),
+                bytes => bytes,
             }
+        } else {
+            self.blob_bytes(delta.new_file().id())?
+        };
+
+        // Git stores a file with the attribute as UTF-8 and converts it on checkout
+        let blob_encoding = attr.map(|_| UTF_8);
+        let new_encoding = if workdir { attr } else { blob_encoding };
+        let decoded = diff_decoded(
+            &parsed.path,
+            old.as_deref().map(|bytes| (bytes, blob_encoding)),
+            new.as_deref().map(|bytes| (bytes, new_encoding)),
+            opts,
+        )?;
+
+        Ok(match decoded {
+            Some(decoded) => FileDiff {
+                hunks: decoded.hunks,
+                is_binary: false,
+                encoding: decoded.encoding,
+                has_bom: decoded.has_bom,
+                line_endings_only: decoded.line_endings_only,
+                ..parsed
+            },
+            None => parsed,
+        })
+    }
+
+    /// The repository's git config, including the user's and system-wide settings.
+    pub fn git_config(&self) -> Option<git2::Config> {
+        self.repo.config().ok()
+    }
+
+    /// libgit2 options for `settings`, with the repository's git config filling the gaps.
+    fn diff_options(&self, settings: &DiffSettings) -> DiffOptions {
+        settings.to_options(self.git_config().as_ref())
+    }
+
+    /// The encoding named by a file's `working-tree-encoding` attribute, if any.
+    fn working_tree_encoding(&self, file_path: &str) -> Option<&'static Encoding> {
+        self.repo
+            .get_attr(
+                Path::new(file_path),
+                "working-tree-encoding",
+                AttrCheckFlags::default(),
+            )
+            .ok()
+            .flatten()
+            .and_then(text::encoding_for_label)
+    }
+
+    /// Whether either side of a delta git called binary starts like UTF-16 text, reading only
+    /// the start of each.
+    fn sniffs_utf16(
+        &self,
+        delta: &DiffDelta,
+        file_path: &str,
+        workdir: bool,
+    ) -> Result<bool, AppError> {
+        let new = if workdir {
+            self.read_workdir(file_path, text::BINARY_SNIFF_LEN as u64)?
+        } else {
+            self.blob_prefix(delta.new_file().id())?
+        };
+        if new
+            .as_deref()
+            .is_some_and(|bytes| text::sniff_utf16(bytes).is_some())
+        {
+            return Ok(true);
         }
+        let old = self.blob_prefix(delta.old_file().id())?;
+        Ok(old
+            .as_deref()
+            .is_some_and(|bytes| text::sniff_utf16(bytes).is_some()))
+    }
 
-        for delta in workdir_diff.deltas() {
+    /// The first `BINARY_SNIFF_LEN` bytes of a blob. Streamed where the object database can
+    /// (loose objects), so a large blob isn't read whole.
+    fn blob_prefix(&self, id: Oid) -> Result<Option<Vec<u8>>, AppError> {
+        if id.is_zero() {
+            return Ok(None);
+        }
+        let mut bytes = Vec::new();
+        if let Ok((reader, _, _)) = self.repo.odb()?.reader(id) {
+            if reader
+                .take(text::BINARY_SNIFF_LEN as u64)
+                .read_to_end(&mut bytes)
+                .is_ok()
+            {
+                return Ok(Some(bytes));
+            }
+        }
+        let blob = self.repo.find_blob(id)?;
+        let content = blob.content();
+        Ok(Some(
+            content[..content.len().min(text::BINARY_SNIFF_LEN)].to_vec(),
+        ))
+    }
+
+    fn blob_bytes(&self, id: Oid) -> Result<Option<Vec<u8>>, AppError> {
+        if id.is_zero() {
+            return Ok(None);
+        }
+        Ok(Some(self.repo.find_blob(id)?.content().to_vec()))
+    }
+
+    /// Reads up to `limit` bytes of a working tree file; callers read one byte past
+    /// `MAX_UNTRACKED_BYTES` to tell it is too large. Returns `None` if the file doesn't exist.
+    fn read_workdir(&self, file_path: &str, limit: u64) -> Result<Option<Vec<u8>>, AppError> {
+        let workdir = self
+            .repo
+            .workdir()
+            .ok_or_else(|| AppError::Custom("No working directory".to_string()))?;
+        let mut bytes = Vec::new();
+        match File::open(workdir.join(file_path))
+            .and_then(|file| file.take(limit).read_to_end(&mut bytes))
+        {
+            Ok(_) => Ok(Some(bytes)),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(AppError::Custom(format!("Failed to read file: {}", e))),
+        }
+    }
+
+    /// Finds the hunk of a file's staged or unstaged changes containing the given line range
+    /// on one side.
+    pub fn find_hunk(
+        &self,
+        file_path: &str,
+        staged: bool,
+        is_old: bool,
+        start_line: u32,
+        end_line: u32,
+        context_lines: u32,
+    ) -> Result<Option<DiffHunk>, AppError> {
+        // A staged diff needs HEAD, which a fresh repository doesn't have yet
+        let settings = DiffSettings::with_context(context_lines);
+        let Ok(diff) = self.get_file_diff(file_path, staged, &settings) else {
+            return Ok(None);
+        };
+
+        Ok(diff
+            .hunks
+            .into_iter()
+            .find(|hunk| hunk.covers(is_old, start_line, end_line)))
+    }
+
+    /// Reads a file's content from HEAD, the index or the working tree. A working-tree file
+    /// over `MAX_UNTRACKED_BYTES` isn't read.
+    pub fn read_file(&self, file_path: &str, source: FileSource) -> Result<FileContent, AppError> {
+        let bytes = match source {
+            FileSource::Head => {
+                let Some(tree) = self.repo.head().ok().and_then(|h| h.peel_to_tree().ok()) else {
+                    return Ok(FileContent::Missing);
+                };
+                let Ok(entry) = tree.get_path(Path::new(file_path)) else {
+                    return Ok(FileContent::Missing);
+                };
+                self.repo.find_blob(entry.id())?.content().to_vec()
+            }
+            FileSource::Index => {
+                let index = self.index()?;
+                let Some(entry) = index.get_path(Path::new(file_path), 0) else {
+                    return Ok(FileContent::Missing);
+                };
+                self.repo.find_blob(entry.id)?.content().to_vec()
+            }
+            FileSource::Workdir => match self.read_workdir(file_path, MAX_UNTRACKED_BYTES + 1)? {
+                Some(bytes) if bytes.len() as u64 > MAX_UNTRACKED_BYTES => {
+                    return Ok(FileContent::TooLarge)
+                }
+                Some(bytes) => bytes,
+                None => return Ok(FileContent::Missing),
+            },
+        };
+
+        let attr = self.working_tree_encoding(file_path);
+        let encoding = match source {
+            FileSource::Workdir => attr,
+            FileSource::Head | FileSource::Index => attr.map(|_| UTF_8),
+        };
+        Ok(FileContent::Text(match text::decode(&bytes, encoding) {
+            Some(decoded) => decoded.text.into_owned(),
+            None => String::from_utf8_lossy(&bytes).into_owned(),
+        }))
+    }
+
+    /// Applies a unified diff to the working tree. Fails without touching any file if a
+    /// hunk no longer applies cleanly.
+    pub fn apply_to_workdir(&self, patch: &str) -> Result<(), AppError> {
+        let diff = Diff::from_buffer(patch.as_bytes())?;
+        self.repo.apply(&diff, ApplyLocation::WorkDir, None)?;
+        Ok(())
+    }
+
+    /// Applies a unified diff to the working tree, the index or both. Every file and hunk
+    /// is checked first; if any fail, nothing is applied and the failures are reported.
+    pub fn apply_patch(
+        &self,
+        patch: &str,
+        target: PatchTarget,
+    ) -> Result<PatchApplyResult, AppError> {
+        let diff = Diff::from_buffer(normalize_patch(patch).as_bytes())?;
+        let location = match target {
+            PatchTarget::Workdir => ApplyLocation::WorkDir,
+            PatchTarget::Index => ApplyLocation::Index,
+            PatchTarget::Both => ApplyLocation::Both,
+        };
+
+        let conflicts = self.patch_conflicts(&diff, location);
+        if !conflicts.is_empty() {
+            return Ok(PatchApplyResult {
+                applied: false,
+                conflicts,
+            });
+        }
+
+        self.repo.apply(&diff, location, None)?;
+        Ok(PatchApplyResult {
+            applied: true,
+            conflicts,
+        })
+    }
+
+    /// Dry-runs each file of the patch, then each hunk of any file that fails, so the
+    /// report points at the hunks that no longer match.
+    fn patch_conflicts(&self, diff: &Diff, location: ApplyLocation) -> Vec<PatchConflict> {
+        let mut conflicts = Vec::new();
+
+        for (file_index, delta) in diff.deltas().enumerate() {
             let path = delta
                 .new_file()
                 .path()
@@ -193,14 +531,70 @@ impl GitRepository {
                 .map(|p| p.to_string_lossy().to_string())
                 .unwrap_or_default();
 
-            if !diffs.iter().any(|d| d.path == path) {
-                if let Ok(diff) = self.parse_diff(&workdir_diff, &path) {
-                    diffs.push(diff);
+            let Err(file_error) = self.check_apply(diff, location, file_index, None) else {
+                continue;
+            };
+
+            let hunk_count = git2::Patch::from_diff(diff, file_index)
+                .ok()
+                .flatten()
+                .map(|p| p.num_hunks())
+                .unwrap_or(0);
+            let mut failed_hunks = Vec::new();
+            for hunk_index in 0..hunk_count {
+                if let Err(e) = self.check_apply(diff, location, file_index, Some(hunk_index)) {
+                    let header = git2::Patch::from_diff(diff, file_index)
+                        .ok()
+                        .flatten()
+                        .and_then(|p| {
+                            p.hunk(hunk_index).ok().map(|(h, _)| {
+                                String::from_utf8_lossy(h.header()).trim().to_string()
+                            })
+                        });
+                    failed_hunks.push(PatchConflict {
+                        path: path.clone(),
+                        hunk: header,
+                        message: e.message().to_string(),
+                    });
                 }
             }
+
+            // A file-level failure (missing file, already exists) fails even with no hunks
+            if failed_hunks.is_empty() {
+                conflicts.push(PatchConflict {
+                    path,
+                    hunk: None,
+                    message: file_error.message().to_string(),
+                });
+            } else {
+                conflicts.extend(failed_hunks);
+            }
         }
 
-        Ok(diffs)
+        conflicts
+    }
+
+    /// Checks whether one file (optionally one hunk of it) of the diff applies cleanly.
+    fn check_apply(
+        &self,
+        diff: &Diff,
+        location: ApplyLocation,
+        file_index: usize,
+        hunk_index: Option<usize>,
+    ) -> Result<(), git2::Error> {
+        let mut delta_seen = 0;
+        let mut hunk_seen = 0;
+        let mut opts = ApplyOptions::new();
+        opts.check(true);
+        opts.delta_callback(move |_| {
+            delta_seen += 1;
+            delta_seen - 1 == file_index
+        });
+        opts.hunk_callback(move |_| {
+            hunk_seen += 1;
+            hunk_index.is_none_or(|i| hunk_seen - 1 == i)
+        });
+        self.repo.apply(diff, location, Some(&mut opts))
     }
 
     fn get_file_status(&self, file_path: &str, staged: bool) -> Result<FileStatus, AppError> {
@@ -228,23 +622,51 @@ impl GitRepository {
         file_path: &str,
         status: FileStatus,
     ) -> Result<FileDiff, AppError> {
-        let workdir = self
-            .repo
-            .workdir()
-            .ok_or_else(|| AppError::Custom("No working directory".to_string()))?;
+        let empty = FileDiff {
+            path: file_path.to_string(),
+            old_path: None,
+            status,
+            hunks: vec![],
+            is_binary: false,
+            language: detect_language(file_path),
+            is_large: false,
+            encoding: None,
+            has_bom: false,
+            old_mode: None,
+            new_mode: None,
+            old_link_target: None,
+            new_link_target: None,
+            line_endings_only: false,
+        };
 
-        let full_path = workdir.join(file_path);
-        let content = std::fs::read_to_string(&full_path)
-            .map_err(|e| AppError::Custom(format!("Failed to read file: {}", e)))?;
+        // Agents leave logs and build output lying around; never read more than the cap
+        let bytes = self
+            .read_workdir(file_path, MAX_UNTRACKED_BYTES + 1)?
+            .ok_or_else(|| AppError::Custom(format!("File not found: {}", file_path)))?;
+        if bytes.len() as u64 > MAX_UNTRACKED_BYTES {
+            return Ok(FileDiff {
+                is_large: true,
+                ..empty
+            });
+        }
+        let Some(decoded) = text::decode(&bytes, self.working_tree_encoding(file_path)) else {
+            return Ok(FileDiff {
+                is_binary: true,
+                ..empty
+            });
+        };
 
-        let lines: Vec<DiffLine> = content
-            .lines()
+        let lines: Vec<DiffLine> = decoded
+            .text
+            .split_inclusive('\n')
             .enumerate()
             .map(|(i, line)| DiffLine {
                 line_type: LineType::Addition,
-                content: format!("{}\n", line),
+                content: line.to_string(),
                 old_line_no: None,
                 new_line_no: Some((i + 1) as u32),
+                no_newline: !line.ends_with('\n'),
+                moved: None,
             })
             .collect();
 
@@ -257,150 +679,19 @@ impl GitRepository {
             new_start: 1,
             new_lines: line_count,
             lines,
+            omitted_lines: 0,
         };
 
         Ok(FileDiff {
-            path: file_path.to_string(),
-            old_path: None,
-            status,
             hunks: vec![hunk],
-            is_binary: false,
-            language: detect_language(file_path),
-        })
-    }
-
-    fn parse_diff(&self, diff: &Diff, file_path: &str) -> Result<FileDiff, AppError> {
-        let mut hunks = Vec::new();
-        let mut status = FileStatus::Modified;
-        let mut old_path = None;
-        let mut is_binary = false;
-
-        for delta in diff.deltas() {
-            let delta_path = delta
-                .new_file()
-                .path()
-                .or_else(|| delta.old_file().path())
-                .map(|p| p.to_string_lossy().to_string())
-                .unwrap_or_default();
-
-            if delta_path != file_path {
-                continue;
-            }
-
-            is_binary = delta.flags().is_binary();
-            status = match delta.status() {
-                Delta::Added => FileStatus::Added,
-                Delta::Deleted => FileStatus::Deleted,
-                Delta::Modified => FileStatus::Modified,
-                Delta::Renamed => {
-                    old_path = delta
-                        .old_file()
-                        .path()
-                        .map(|p| p.to_string_lossy().to_string());
-                    FileStatus::Renamed
-                }
-                Delta::Copied => FileStatus::Copied,
-                _ => FileStatus::Modified,
-            };
-        }
-
-        if is_binary {
-            return Ok(FileDiff {
-                path: file_path.to_string(),
-                old_path,
-                status,
-                hunks: vec![],
-                is_binary: true,
-                language: detect_language(file_path),
-            });
-        }
-
-        let mut current_hunk_lines: Vec<DiffLine> = Vec::new();
-        let mut current_hunk_header = String::new();
-        let mut hunk_old_start = 0u32;
-        let mut hunk_old_lines = 0u32;
-        let mut hunk_new_start = 0u32;
-        let mut hunk_new_lines = 0u32;
-        let mut last_hunk_id: Option<(u32, u32)> = None;
-
-        diff.print(git2::DiffFormat::Patch, |delta, hunk, line| {
-            let delta_path = delta
-                .new_file()
-                .path()
-                .or_else(|| delta.old_file().path())
-                .map(|p| p.to_string_lossy().to_string())
-                .unwrap_or_default();
-
-            if delta_path != file_path {
-                return true;
-            }
-
-            if let Some(h) = hunk {
-                let hunk_id = (h.old_start(), h.new_start());
-
-                // Only start a new hunk if the hunk actually changed
-                if last_hunk_id != Some(hunk_id) {
-                    if !current_hunk_lines.is_empty() {
-                        hunks.push(DiffHunk {
-                            header: current_hunk_header.clone(),
-                            old_start: hunk_old_start,
-                            old_lines: hunk_old_lines,
-                            new_start: hunk_new_start,
-                            new_lines: hunk_new_lines,
-                            lines: std::mem::take(&mut current_hunk_lines),
-                        });
-                    }
-
-                    current_hunk_header = String::from_utf8_lossy(h.header()).trim().to_string();
-                    hunk_old_start = h.old_start();
-                    hunk_old_lines = h.old_lines();
-                    hunk_new_start = h.new_start();
-                    hunk_new_lines = h.new_lines();
-                    last_hunk_id = Some(hunk_id);
-                }
-            }
-
-            let content = String::from_utf8_lossy(line.content()).to_string();
-            let (line_type, old_line_no, new_line_no) = match line.origin() {
-                '+' => (LineType::Addition, None, line.new_lineno()),
-                '-' => (LineType::Deletion, line.old_lineno(), None),
-                ' ' => (LineType::Context, line.old_lineno(), line.new_lineno()),
-                _ => return true,
-            };
-
-            current_hunk_lines.push(DiffLine {
-                line_type,
-                content,
-                old_line_no,
-                new_line_no,
-            });
-
-            true
-        })?;
-
-        if !current_hunk_lines.is_empty() {
-            hunks.push(DiffHunk {
-                header: current_hunk_header,
-                old_start: hunk_old_start,
-                old_lines: hunk_old_lines,
-                new_start: hunk_new_start,
-                new_lines: hunk_new_lines,
-                lines: current_hunk_lines,
-            });
-        }
-
-        Ok(FileDiff {
-            path: file_path.to_string(),
-            old_path,
-            status,
-            hunks,
-            is_binary,
-            language: detect_language(file_path),
+            encoding: Some(decoded.encoding.name().to_string()),
+            has_bom: decoded.bom,
+            ..empty
         })
     }
 
     pub fn stage_file(&self, file_path: &str) -> Result<(), AppError> {
-        let mut index = self.repo.index()?;
+        let mut index = self.index()?;
         let workdir = self
             .repo
             .workdir()
@@ -426,7 +717,7 @@ impl GitRepository {
     }
 
     pub fn stage_all(&self) -> Result<(), AppError> {
-        let mut index = self.repo.index()?;
+        let mut index = self.index()?;
         index.add_all(["*"].iter(), IndexAddOption::DEFAULT, None)?;
         index.write()?;
         Ok(())
@@ -442,7 +733,7 @@ impl GitRepository {
                     .reset(&commit.into_object(), ResetType::Mixed, None)?;
             }
             Err(_) => {
-                let mut index = self.repo.index()?;
+                let mut index = self.index()?;
                 index.clear()?;
                 index.write()?;
             }
@@ -452,7 +743,7 @@ impl GitRepository {
     }
 
     pub fn commit(&self, message: &str) -> Result<String, AppError> {
-        let mut index = self.repo.index()?;
+        let mut index = self.index()?;
         let tree_id = index.write_tree()?;
         let tree = self.repo.find_tree(tree_id)?;
 
@@ -531,7 +822,7 @@ impl GitRepository {
     }
 }
 
-fn detect_language(path: &str) -> Option<String> {
+pub fn detect_language(path: &str) -> Option<String> {
     let ext = Path::new(path).extension()?.to_str()?;
     let lang = match ext.to_lowercase().as_str() {
         "rs" => "rust",
@@ -0,0 +1,218 @@
+use chardetng::EncodingDetector;
+use encoding_rs::{Encoding, UTF_16BE, UTF_16LE, UTF_8};
+use std::borrow::Cow;
+
+/// How much of a file is checked for NUL bytes, the same heuristic git uses.
+pub(crate) const BINARY_SNIFF_LEN: usize = 8000;
+
+/// Whether content looks binary, i.e. has a NUL byte near the start.
+pub fn is_binary(bytes: &[u8]) -> bool {
+    bytes[..bytes.len().min(BINARY_SNIFF_LEN)].contains(&0)
+}
+
+/// Bytes of UTF-16 without a byte order mark needed before its NULs are taken as text rather
+/// than binary, so a few stray bytes can't pass for it.
+const MIN_UTF16_SAMPLE: usize = 16;
+
+/// The UTF-16 encoding content that git calls binary looks like it is in, going by its start:
+/// a UTF-16 byte order mark, or NULs on one side of nearly every other pair of bytes, as
+/// mostly-ASCII text has, that decode to text with paired surrogates and few control
+/// characters. Only the first few KiB are looked at, so a prefix is enough.
+pub fn sniff_utf16(bytes: &[u8]) -> Option<&'static Encoding> {
+    match Encoding::for_bom(bytes) {
+        Some((encoding, _)) if encoding == UTF_16LE || encoding == UTF_16BE => {
+            return Some(encoding)
+        }
+        _ => {}
+    }
+    let sample = &bytes[..bytes.len().min(BINARY_SNIFF_LEN) & !1];
+    if sample.len() < MIN_UTF16_SAMPLE {
+        return None;
+    }
+
+    let (mut low, mut high) = (0, 0);
+    for pair in sample.chunks_exact(2) {
+        match (pair[0], pair[1]) {
+            (0, 0) => return None,
+            (_, 0) => low += 1,
+            (0, _) => high += 1,
+            _ => {}
+        }
+    }
+    let total = sample.len() / 2;
+    let (most, fewest) = (low.max(high), low.min(high));
+    if most * 2 < total || fewest * 8 > most {
+        return None;
+    }
+
+    let little_endian = low > high;
+    let units = sample.chunks_exact(2).map(|pair| {
+        if little_endian {
+            u16::from_le_bytes([pair[0], pair[1]])
+        } else {
+            u16::from_be_bytes([pair[0], pair[1]])
+        }
+    });
+    looks_like_text(units, total).then_some(if little_endian { UTF_16LE } else { UTF_16BE })
+}
+
+/// Whether UTF-16 code units read as text: every surrogate paired (bar a high one cut off at
+/// the end of the sample), and at most one in twenty a control character other than a tab,
+/// line feed, carriage return or form feed.
+fn looks_like_text(units: impl Iterator<Item = u16>, total: usize) -> bool {
+    let mut controls = 0;
+    let mut pending_high = false;
+    for unit in units {
+        match unit {
+            0xD800..=0xDBFF if pending_high => return false,
+            0xD800..=0xDBFF => pending_high = true,
+            0xDC00..=0xDFFF if !pending_high => return false,
+            _ if pending_high && !(0xDC00..=0xDFFF).contains(&unit) => return false,
+            _ => {
+                pending_high = false;
+                let control = unit < 0x20 && !matches!(unit, 0x09 | 0x0A | 0x0C | 0x0D);
+                if control || unit == 0x7F {
+                    controls += 1;
+                }
+            }
+        }
+    }
+    controls * 20 <= total
+}
+
+/// File content decoded for display.
+pub struct Decoded<'a> {
+    pub text: Cow<'a, str>,
+    pub encoding: &'static Encoding,
+    /// Whether the content started with a byte order mark, which isn't part of `text`.
+    pub bom: bool,
+}
+
+/// Decodes file content for display. A byte order mark decides the encoding; otherwise the
+/// given one (from a `working-tree-encoding` attribute) is used, or UTF-16 if the content has
+/// NULs laid out like it, or UTF-8 if the content is valid UTF-8, or whatever encoding it
+/// most likely uses, e.g. Windows-1252 for a Latin-1 file. Returns `None` for binary content.
+pub fn decode<'a>(bytes: &'a [u8], encoding: Option<&'static Encoding>) -> Option<Decoded<'a>> {
+    if let Some((encoding, bom_len)) = Encoding::for_bom(bytes) {
+        let (text, _) = encoding.decode_without_bom_handling(&bytes[bom_len..]);
+        return Some(Decoded {
+            text,
+            encoding,
+            bom: true,
+        });
+    }
+
+    // UTF-16 without a BOM is full of NULs, so it has to look the part throughout its start
+    let encoding = match encoding {
+        Some(encoding) => encoding,
+        None if is_binary(bytes) => sniff_utf16(bytes).filter(|_| bytes.len().is_multiple_of(2))?,
+        None if std::str::from_utf8(bytes).is_ok() => UTF_8,
+        None => {
+            let mut detector = EncodingDetector::new();
+            detector.feed(bytes, true);
+            detector.guess(None, true)
+        }
+    };
+    let (text, _) = encoding.decode_without_bom_handling(bytes);
+    Some(Decoded {
+        text,
+        encoding,
+        bom: false,
+    })
+}
+
+/// Looks up an encoding by a label such as `UTF-16LE`, `Shift_JIS` or `latin1`.
+pub fn encoding_for_label(label: &str) -> Option<&'static Encoding> {
+    Encoding::for_label(label.trim().as_bytes())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn utf16le(text: &str) -> Vec<u8> {
+        text.encode_utf16().flat_map(u16::to_le_bytes).collect()
+    }
+
+    fn utf16be(text: &str) -> Vec<u8> {
+        text.encode_utf16().flat_map(u16::to_be_bytes).collect()
+    }
+
+    #[test]
+    fn binary_means_a_nul_near_the_start() {
+        assert!(is_binary(b"a\0b"));
+        assert!(!is_binary(b"plain text\n"));
+        let mut late = vec![b'a'; BINARY_SNIFF_LEN];
+        late.push(0);
+        assert!(!is_binary(&late));
+    }
+
+    #[test]
+    fn sniffs_utf16_without_bom() {
+        let text = "fn main() {\n    println!(\"hi\");\n}\n";
+        assert_eq!(sniff_utf16(&utf16le(text)), Some(UTF_16LE));
+        assert_eq!(sniff_utf16(&utf16be(text)), Some(UTF_16BE));
+        assert_eq!(
+            sniff_utf16(&utf16le("emoji 😀 in a line\n")),
+            Some(UTF_16LE)
+        );
+    }
+
+    #[test]
+    fn sniffs_utf16_bom_however_short() {
+        assert_eq!(sniff_utf16(b"\xFF\xFEa\0"), Some(UTF_16LE));
+        assert_eq!(sniff_utf16(b"\xFE\xFF\0a"), Some(UTF_16BE));
+    }
+
+    #[test]
+    fn rejects_short_or_binary_samples() {
+        assert_eq!(sniff_utf16(b"\x00\x01\x02"), None);
+        assert_eq!(sniff_utf16(&utf16le("short\n")), None);
+        // NULs on both sides, as in most binary formats
+        assert_eq!(
+            sniff_utf16(b"\x89PNG\r\n\x1a\n\0\0\0\rIHDR\0\0\x01\0\0\0\x01\0"),
+            None
+        );
+        let controls: Vec<u8> = (1..=20u8).flat_map(|b| [b, 0]).collect();
+        assert_eq!(sniff_utf16(&controls), None);
+    }
+
+    #[test]
+    fn rejects_unpaired_surrogates() {
+        let mut bytes = utf16le("some text here\n");
+        bytes.splice(4..4, 0xDC00u16.to_le_bytes());
+        assert_eq!(sniff_utf16(&bytes), None);
+        let mut bytes = utf16le("some text here\n");
+        bytes.splice(4..4, 0xD800u16.to_le_bytes());
+        assert_eq!(sniff_utf16(&bytes), None);
+    }
+
+    #[test]
+    fn decodes_by_bom_attribute_or_content() {
+        let decoded = decode(b"\xEF\xBB\xBFhi\n", None).unwrap();
+        assert_eq!(
+            (decoded.text.as_ref(), decoded.encoding, decoded.bom),
+            ("hi\n", UTF_8, true)
+        );
+
+        let decoded = decode(b"x\0\n\0", Some(UTF_16LE)).unwrap();
+        assert_eq!((decoded.text.as_ref(), decoded.bom), ("x\n", false));
+
+        let text = "no byte order mark here\n";
+        let bytes = utf16be(text);
+        let decoded = decode(&bytes, None).unwrap();
+        assert_eq!((decoded.text.as_ref(), decoded.encoding), (text, UTF_16BE));
+
+        assert_eq!(decode("café\n".as_bytes(), None).unwrap().encoding, UTF_8);
+        let latin1 = decode(b"caf\xe9 cr\xe8me br\xfbl\xe9e\n", None).unwrap();
+        assert_eq!(latin1.text, "café crème brûlée\n");
+    }
+
+    #[test]
+    fn leaves_binary_undecoded() {
+        assert!(decode(b"\x00\x01\x02", None).is_none());
+        let mut odd = utf16le("an odd number of bytes\n");
+        odd.push(b'x');
+        assert!(decode(&odd, None).is_none());
+    }
+}
@@ -38,6 +38,26 @@ pub struct DiffLine {
     pub content: String,
     pub old_line_no: Option<u32>,
     pub new_line_no: Option<u32>,
+    /// The line is the last of its side and has no newline at the end (git's
+    /// `\ No newline at end of file`).
+    #[serde(default)]
+    pub no_newline: bool,
+    /// Set when the line is part of a block of code moved elsewhere in the diff: for an added
+    /// line, where it was deleted, and for a deleted line, where it was added.
+    #[serde(default)]
+    pub moved: Option<LineMove>,
+}
+
+/// The other end of a moved line.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LineMove {
+    pub path: String,
+    /// The counterpart's line number on its own side: old for a deleted line, new for an
+    /// added one.
+    pub line: u32,
+    /// The block was changed slightly along the way.
+    pub edited: bool,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -49,6 +69,39 @@ pub struct DiffHunk {
     pub new_start: u32,
     pub new_lines: u32,
     pub lines: Vec<DiffLine>,
+    /// How many of the hunk's lines were left out of `lines` after them, for a diff over
+    /// the size limits; they are fetched a page at a time with `get_diff_hunk`.
+    #[serde(default)]
+    pub omitted_lines: usize,
+}
+
+impl DiffHunk {
+    /// Up to `limit` of the hunk's lines from `offset`, with the rest counted as omitted.
+    pub fn page(&self, offset: usize, limit: usize) -> DiffHunk {
+        let end = offset.saturating_add(limit).min(self.lines.len());
+        let start = offset.min(end);
+        DiffHunk {
+            header: self.header.clone(),
+            old_start: self.old_start,
+            old_lines: self.old_lines,
+            new_start: self.new_start,
+            new_lines: self.new_lines,
+            lines: self.lines[start..end].to_vec(),
+            omitted_lines: self.lines.len() - end,
+        }
+    }
+
+    /// Whether any of the hunk's lines fall in the given line range on one side.
+    pub fn covers(&self, is_old: bool, start_line: u32, end_line: u32) -> bool {
+        self.lines.iter().any(|line| {
+            let line_no = if is_old {
+                line.old_line_no
+            } else {
+                line.new_line_no
+            };
+            line_no.is_some_and(|n| n >= start_line && n <= end_line)
+        })
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -60,6 +113,55 @@ pub struct FileDiff {
     pub hunks: Vec<DiffHunk>,
     pub is_binary: bool,
     pub language: Option<String>,
+    /// Set when the diff was over the size limits and its hunks were sent without their
+    /// lines, to be fetched one at a time.
+    #[serde(default)]
+    pub is_large: bool,
+    /// The encoding the file's text was decoded from, e.g. `UTF-8` or `Shift_JIS`. `None`
+    /// for binary files, or when the diff couldn't be decoded as a whole and its lines were
+    /// read as UTF-8 with invalid bytes replaced.
+    #[serde(default)]
+    pub encoding: Option<String>,
+    /// Whether the file starts with a byte order mark (not included in the lines).
+    #[serde(default)]
+    pub has_bom: bool,
+    /// Git file modes, e.g. `0o100644`, `0o100755` for an executable or `0o120000` for a
+    /// symlink. `None` for a side the file doesn't exist on, or when the diff doesn't say.
+    #[serde(default)]
+    pub old_mode: Option<u32>,
+    #[serde(default)]
+    pub new_mode: Option<u32>,
+    /// Where each side points, for a symlink.
+    #[serde(default)]
+    pub old_link_target: Option<String>,
+    #[serde(default)]
+    pub new_link_target: Option<String>,
+    /// Every changed line differs only in its line ending (CRLF against LF, or a newline
+    /// added or removed at the end of the file).
+    #[serde(default)]
+    pub line_endings_only: bool,
+}
+
+impl FileDiff {
+    /// Whether the diff has more than `max_lines` lines across its hunks, or more than
+    /// `max_bytes` of line content.
+    pub fn exceeds(&self, max_lines: usize, max_bytes: usize) -> bool {
+        let lines = self.hunks.iter().flat_map(|hunk| &hunk.lines);
+        let (count, bytes) = lines.fold((0, 0), |(count, bytes), line| {
+            (count + 1, bytes + line.content.len())
+        });
+        count > max_lines || bytes > max_bytes
+    }
+
+    /// Drops every hunk's lines, keeping its header, ranges and line count as a summary, and
+    /// marks the diff as large.
+    pub fn collapse(&mut self) {
+        for hunk in &mut self.hunks {
+            hunk.omitted_lines = hunk.lines.len();
+            hunk.lines = Vec::new();
+        }
+        self.is_large = true;
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -71,3 +173,49 @@ pub struct RepositoryStatus {
     pub staged_count: usize,
     pub unstaged_count: usize,
 }
+
+/// Which version of a file to read.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FileSource {
+    Head,
+    Index,
+    Workdir,
+}
+
+/// A version of a file read back to check comments against.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FileContent {
+    Text(String),
+    /// The file doesn't exist in that version.
+    Missing,
+    /// The file is over `MAX_UNTRACKED_BYTES`, so it wasn't read.
+    TooLarge,
+}
+
+/// Where an imported patch is applied.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum PatchTarget {
+    Workdir,
+    Index,
+    Both,
+}
+
+/// A file or hunk of a patch that doesn't apply to the current tree.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PatchConflict {
+    pub path: String,
+    /// Header of the failing hunk, or `None` when the file itself is the problem
+    /// (missing, already exists, binary).
+    pub hunk: Option<String>,
+    pub message: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PatchApplyResult {
+    /// Patches are applied all-or-nothing: `false` means nothing was changed.
+    pub applied: bool,
+    pub conflicts: Vec<PatchConflict>,
+}
@@ -1,21 +1,51 @@
+mod cache;
+mod cli;
 mod commands;
+mod compare;
+mod config;
 mod error;
 mod git;
+mod requests;
+mod review;
+mod watcher;
 
+use cache::RepoCache;
+use cli::LaunchOptions;
 use commands::*;
+use requests::RequestRegistry;
 use std::env;
-use tauri::Emitter;
+use std::sync::Mutex;
+use tauri::{Emitter, Manager};
+
+/// Keeps the agent reply watcher alive for the lifetime of the app.
+struct ReplyWatcher(#[allow(dead_code)] Mutex<notify::RecommendedWatcher>);
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
-    // Collect CLI arguments - first arg after binary name is the repo path
-    let args: Vec<String> = env::args().collect();
-    let initial_repo_path = args.get(1).cloned();
+    // Collect CLI arguments - first non-flag arg after binary name is the repo path
+    let launch = LaunchOptions::parse(env::args());
+    let initial_repo_path = launch.repo_path.clone();
+    let initial_patch = match launch.read_patch() {
+        Some(Ok(patch)) => Some(patch),
+        Some(Err(e)) => {
+            eprintln!("Failed to read patch: {}", e);
+            None
+        }
+        None => None,
+    };
+    let launch_patch_name = launch.patch_name();
+    let initial_compare = launch.compare.clone();
+    let excludes = launch.excludes.clone();
+    let repos = RepoCache::default();
 
     tauri::Builder::default()
         .plugin(tauri_plugin_shell::init())
         .plugin(tauri_plugin_clipboard_manager::init())
         .plugin(tauri_plugin_dialog::init())
+        .manage(launch)
+        .manage(repos.clone())
+        .manage(RequestRegistry::default())
+        .manage(RepoWatchState::default())
         .setup(move |app| {
             // If a repo path was provided via CLI, emit it to the frontend
             if let Some(ref path) = initial_repo_path {
@@ -28,13 +58,63 @@ pub fn run() {
                 std::thread::spawn(move || {
                     std::thread::sleep(std::time::Duration::from_millis(500));
                     let _ = handle.emit("open-repo", resolved_path);
+                    // A patch given with --patch is previewed against the opened repo
+                    if let Some(patch) = initial_patch {
+                        let _ = handle.emit("import-patch", patch);
+                    }
+                });
+            } else if let Some((left, right)) = initial_compare {
+                let handle = app.handle().clone();
+                std::thread::spawn(move || {
+                    std::thread::sleep(std::time::Duration::from_millis(500));
+                    match compare::DirComparison::create(&left, &right, excludes) {
+                        Ok(comparison) => {
+                            let _ = handle.emit("compare-dirs", comparison);
+                        }
+                        Err(e) => eprintln!("Failed to compare directories: {}", e),
+                    }
+                });
+            } else if let Some(patch) = initial_patch {
+                // No repository: review the patch on its own
+                let name = launch_patch_name.unwrap_or_else(|| "patch".to_string());
+                let handle = app.handle().clone();
+                std::thread::spawn(move || {
+                    std::thread::sleep(std::time::Duration::from_millis(500));
+                    match commands::patch::open_patch(&name, patch) {
+                        Ok(opened) => {
+                            let _ = handle.emit("review-patch", opened);
+                        }
+                        Err(e) => eprintln!("Failed to open patch: {}", e),
+                    }
                 });
             }
+
+            // Watch for agents replying to exported reviews, once the frontend is listening
+            let handle = app.handle().clone();
+            std::thread::spawn(move || {
+                std::thread::sleep(std::time::Duration::from_millis(500));
+                let emitter = handle.clone();
+                let watcher = review::replies::watch(repos, move |batch| match batch {
+                    Ok(batch) => {
+                        let _ = emitter.emit("agent-replies", batch);
+                    }
+                    Err(e) => eprintln!("Failed to ingest agent reply: {}", e),
+                });
+                match watcher {
+                    Ok(watcher) => {
+                        handle.manage(ReplyWatcher(Mutex::new(watcher)));
+                    }
+                    Err(e) => eprintln!("Failed to watch for agent replies: {}", e),
+                }
+            });
             Ok(())
         })
         .invoke_handler(tauri::generate_handler![
             get_status,
+            cancel_request,
             get_file_diff,
+            get_diff_hunk,
+            get_diff_settings,
             get_combined_diff,
             stage_file,
             unstage_file,
@@ -43,7 +123,31 @@ pub fn run() {
             commit,
             discard_file,
             discard_all,
+            list_comments,
+            add_comment,
+            update_comment,
+            delete_comment,
+            add_comment_reply,
+            delete_comment_reply,
+            resolve_comment,
+            apply_suggestion,
+            undo_suggestion,
+            import_patch,
+            apply_patch,
+            open_patch_review,
+            open_comparison,
+            clear_comments,
+            import_comments,
+            apply_agent_replies,
+            check_review,
             export_review,
+            render_review,
+            list_review_history,
+            open_review_history,
+            reexport_review,
+            prune_review_history,
+            watch_repository,
+            unwatch_repository,
         ])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
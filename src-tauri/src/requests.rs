@@ -0,0 +1,68 @@
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+
+use crate::error::AppError;
+
+/// Set once the request it was handed to has been superseded or cancelled by the frontend.
+#[derive(Clone, Default)]
+pub struct CancelToken(Arc<AtomicBool>);
+
+impl CancelToken {
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::Relaxed)
+    }
+
+    /// Fails with [`AppError::Cancelled`] if the request should stop.
+    pub fn check(&self) -> Result<(), AppError> {
+        if self.is_cancelled() {
+            return Err(AppError::Cancelled);
+        }
+        Ok(())
+    }
+
+    fn cancel(&self) {
+        self.0.store(true, Ordering::Relaxed);
+    }
+}
+
+/// The requests in flight, by the key the frontend gave them (e.g. `"file-diff"`). Starting a
+/// request cancels the previous one under the same key, so only the latest one finishes.
+#[derive(Clone, Default)]
+pub struct RequestRegistry(Arc<Mutex<HashMap<String, CancelToken>>>);
+
+impl RequestRegistry {
+    /// Registers a request, cancelling any earlier one under `key`. Requests without a key
+    /// can't be superseded.
+    pub fn begin(&self, key: Option<&str>) -> CancelToken {
+        let token = CancelToken::default();
+        if let Some(key) = key {
+            if let Some(previous) = self.lock().insert(key.to_string(), token.clone()) {
+                previous.cancel();
+            }
+        }
+        token
+    }
+
+    /// Forgets a finished request, unless a newer one has taken its key.
+    pub fn finish(&self, key: Option<&str>, token: &CancelToken) {
+        let Some(key) = key else { return };
+        let mut requests = self.lock();
+        if requests
+            .get(key)
+            .is_some_and(|current| Arc::ptr_eq(&current.0, &token.0))
+        {
+            requests.remove(key);
+        }
+    }
+
+    pub fn cancel(&self, key: &str) {
+        if let Some(token) = self.lock().remove(key) {
+            token.cancel();
+        }
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, HashMap<String, CancelToken>> {
+        self.0.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
@@ -0,0 +1,391 @@
+use serde::Serialize;
+use std::fs;
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+use std::process::{Child, Command, ExitStatus, Stdio};
+use std::sync::mpsc;
+use std::time::{Duration, Instant};
+
+use crate::config::{home_dir, is_confined, map_path, Destination, PathMapping, TmuxPayload};
+use crate::error::AppError;
+
+/// How long a pipe's reader or a command gets to take the review before the delivery fails,
+/// so one stuck destination can't hold up an export indefinitely.
+const DELIVERY_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// What a path template and the delivery adapters need to know about the export.
+pub struct DeliveryContext<'a> {
+    pub repo_root: &'a Path,
+    pub repo_name: &'a str,
+    pub epoch: u64,
+    pub extension: &'a str,
+    pub path_mappings: &'a [PathMapping],
+    /// The destinations came from the repository's config, so files may only be written
+    /// inside the repository or `~/.revu`.
+    pub confined: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DeliveryReport {
+    pub destination: String,
+    pub success: bool,
+    pub message: String,
+    /// The written file as the agent sees it, after path mapping.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub path: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExportResult {
+    /// The first file the review was written to, for copying to the clipboard.
+    pub path: Option<String>,
+    pub deliveries: Vec<DeliveryReport>,
+    /// Host paths of every file written, for the export history.
+    #[serde(skip)]
+    pub written: Vec<PathBuf>,
+}
+
+impl ExportResult {
+    pub fn any_succeeded(&self) -> bool {
+        self.deliveries.iter().any(|d| d.success)
+    }
+}
+
+/// Delivers the rendered review to every destination. File destinations go first so
+/// adapters that pass a path along (tmux, commands) can refer to the written file.
+/// Reported paths are mapped into the agent's view of the filesystem.
+pub fn deliver_all(
+    destinations: &[Destination],
+    ctx: &DeliveryContext,
+    content: &str,
+) -> ExportResult {
+    let (files, others): (Vec<&Destination>, Vec<&Destination>) = destinations
+        .iter()
+        .partition(|d| matches!(d, Destination::File { .. }));
+
+    let mut deliveries = Vec::new();
+    let mut written: Vec<PathBuf> = Vec::new();
+
+    for destination in files.into_iter().chain(others) {
+        let (mut report, host_path) = deliver(
+            destination,
+            ctx,
+            content,
+            written.first().map(PathBuf::as_path),
+        );
+        report.path = host_path.as_deref().map(|p| map_path(ctx.path_mappings, p));
+        written.extend(host_path);
+        deliveries.push(report);
+    }
+
+    ExportResult {
+        path: deliveries.iter().find_map(|d| d.path.clone()),
+        deliveries,
+        written,
+    }
+}
+
+/// Reports a destination from a repository's config that was refused: a pipe, tmux or
+/// command destination (see [`Destination::needs_trust`]), or a file outside the repository
+/// and `~/.revu`.
+pub fn refused_report(destination: &Destination) -> DeliveryReport {
+    DeliveryReport {
+        destination: label(destination),
+        success: false,
+        message: refused_message(destination).to_string(),
+        path: None,
+    }
+}
+
+fn refused_message(destination: &Destination) -> &'static str {
+    match destination {
+        Destination::File { .. } => FILE_REFUSED,
+        _ => {
+            "Not run: pipe, tmux and command destinations are only read from \
+              ~/.revu/config.json, not the repository's config"
+        }
+    }
+}
+
+const FILE_REFUSED: &str =
+    "Not written: the repository's config can only write files inside the repository or ~/.revu";
+
+/// Delivers to one destination, returning the report and the host path of any file written.
+fn deliver(
+    destination: &Destination,
+    ctx: &DeliveryContext,
+    content: &str,
+    written: Option<&Path>,
+) -> (DeliveryReport, Option<PathBuf>) {
+    let label = label(destination);
+    let exported_path = written.map(|p| map_path(ctx.path_mappings, p));
+    let result = match destination {
+        Destination::File { path } => write_file(path, ctx, content).map(Some),
+        Destination::Stdout => write_stdout(content).map(|_| None),
+        Destination::Pipe { path } => write_pipe(Path::new(path), content).map(|_| None),
+        Destination::Tmux {
+            target,
+            send,
+            press_enter,
+        } => {
+            let text = match (send, &exported_path) {
+                (TmuxPayload::Path, Some(path)) => format!("@{}", path),
+                _ => content.to_string(),
+            };
+            send_to_tmux(target, &text, *press_enter).map(|_| None)
+        }
+        Destination::Command { command } => {
+            run_command(command, ctx, content, written, exported_path.as_deref()).map(|_| None)
+        }
+    };
+
+    match result {
+        Ok(path) => {
+            let report = DeliveryReport {
+                message: match &path {
+                    Some(path) => format!("Written to {}", path.display()),
+                    None => "Delivered".to_string(),
+                },
+                destination: label,
+                success: true,
+                path: None,
+            };
+            (report, path)
+        }
+        Err(e) => {
+            let report = DeliveryReport {
+                destination: label,
+                success: false,
+                message: e.to_string(),
+                path: None,
+            };
+            (report, None)
+        }
+    }
+}
+
+fn label(destination: &Destination) -> String {
+    match destination {
+        Destination::File { path } => format!("file {}", path),
+        Destination::Stdout => "stdout".to_string(),
+        Destination::Pipe { path } => format!("pipe {}", path),
+        Destination::Tmux { target, .. } => format!("tmux {}", target),
+        Destination::Command { command } => format!("command `{}`", command),
+    }
+}
+
+/// Expands a destination path template into an absolute path.
+pub fn expand_path(template: &str, ctx: &DeliveryContext) -> Result<PathBuf, AppError> {
+    let home = home_dir()?;
+    let mut expanded = template
+        .replace("{home}", &home.to_string_lossy())
+        .replace("{repo}", &ctx.repo_root.to_string_lossy())
+        .replace("{repo_name}", ctx.repo_name)
+        .replace("{epoch}", &ctx.epoch.to_string())
+        .replace("{ext}", ctx.extension);
+    if expanded == "~" || expanded.starts_with("~/") {
+        expanded = format!("{}{}", home.to_string_lossy(), &expanded[1..]);
+    }
+
+    let path = PathBuf::from(expanded);
+    Ok(if path.is_absolute() {
+        path
+    } else {
+        ctx.repo_root.join(path)
+    })
+}
+
+fn write_file(template: &str, ctx: &DeliveryContext, content: &str) -> Result<PathBuf, AppError> {
+    let path = expand_path(template, ctx)?;
+    if ctx.confined && !is_confined(&path, ctx.repo_root) {
+        return Err(AppError::Custom(FILE_REFUSED.to_string()));
+    }
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+        if parent == ctx.repo_root.join(".revu") {
+            ignore_revu_dir(parent)?;
+        }
+    }
+    fs::write(&path, content)?;
+    Ok(path)
+}
+
+/// Keeps exports written into `<repo>/.revu` from showing up as changes to review.
+fn ignore_revu_dir(dir: &Path) -> Result<(), AppError> {
+    let gitignore = dir.join(".gitignore");
+    if !gitignore.exists() {
+        fs::write(gitignore, "*\n!.gitignore\n")?;
+    }
+    Ok(())
+}
+
+fn write_stdout(content: &str) -> Result<(), AppError> {
+    let mut stdout = std::io::stdout().lock();
+    stdout.write_all(content.as_bytes())?;
+    if !content.ends_with('\n') {
+        stdout.write_all(b"\n")?;
+    }
+    stdout.flush()?;
+    Ok(())
+}
+
+#[cfg(unix)]
+fn write_pipe(path: &Path, content: &str) -> Result<(), AppError> {
+    use std::os::unix::fs::{FileTypeExt, OpenOptionsExt};
+    use std::os::unix::io::AsRawFd;
+
+    if !fs::metadata(path)?.file_type().is_fifo() {
+        return Err(AppError::Custom(format!(
+            "{} is not a named pipe",
+            path.display()
+        )));
+    }
+
+    // Opening non-blocking fails straight away when nobody is reading, instead of hanging
+    let mut pipe = fs::OpenOptions::new()
+        .write(true)
+        .custom_flags(libc::O_NONBLOCK)
+        .open(path)
+        .map_err(|e| match e.raw_os_error() {
+            Some(libc::ENXIO) => {
+                AppError::Custom(format!("No process is reading from {}", path.display()))
+            }
+            _ => e.into(),
+        })?;
+
+    // Stay non-blocking and wait for room in the pipe, so a reader that stops reading makes
+    // the delivery time out instead of hanging
+    let deadline = Instant::now() + DELIVERY_TIMEOUT;
+    let mut remaining = content.as_bytes();
+    while !remaining.is_empty() {
+        match pipe.write(remaining) {
+            Ok(written) => remaining = &remaining[written..],
+            Err(e) if e.kind() == std::io::ErrorKind::WouldBlock => {
+                let left = deadline.saturating_duration_since(Instant::now());
+                if left.is_zero() {
+                    return Err(timed_out());
+                }
+                let mut poll = libc::pollfd {
+                    fd: pipe.as_raw_fd(),
+                    events: libc::POLLOUT,
+                    revents: 0,
+                };
+                unsafe {
+                    libc::poll(&mut poll, 1, left.as_millis().min(i32::MAX as u128) as i32);
+                }
+            }
+            Err(e) if e.kind() == std::io::ErrorKind::Interrupted => {}
+            Err(e) => return Err(e.into()),
+        }
+    }
+    Ok(())
+}
+
+#[cfg(not(unix))]
+fn write_pipe(_path: &Path, _content: &str) -> Result<(), AppError> {
+    Err(AppError::Custom(
+        "Named pipes are only supported on Unix".to_string(),
+    ))
+}
+
+fn send_to_tmux(target: &str, text: &str, press_enter: bool) -> Result<(), AppError> {
+    run_tmux(&["send-keys", "-t", target, "-l", text])?;
+    if press_enter {
+        run_tmux(&["send-keys", "-t", target, "Enter"])?;
+    }
+    Ok(())
+}
+
+fn run_tmux(args: &[&str]) -> Result<(), AppError> {
+    let output = Command::new("tmux").args(args).output()?;
+    if !output.status.success() {
+        return Err(AppError::Custom(format!(
+            "tmux failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        )));
+    }
+    Ok(())
+}
+
+fn run_command(
+    command: &str,
+    ctx: &DeliveryContext,
+    content: &str,
+    written: Option<&Path>,
+    agent_path: Option<&str>,
+) -> Result<(), AppError> {
+    let mut cmd = if cfg!(windows) {
+        let mut cmd = Command::new("cmd");
+        cmd.args(["/C", command]);
+        cmd
+    } else {
+        let mut cmd = Command::new("sh");
+        cmd.args(["-c", command]);
+        cmd
+    };
+    cmd.current_dir(ctx.repo_root)
+        .env("REVU_REPO", ctx.repo_root)
+        .env("REVU_EXPORT_PATH", written.unwrap_or(Path::new("")))
+        .env("REVU_AGENT_EXPORT_PATH", agent_path.unwrap_or_default())
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped());
+
+    let mut child = cmd.spawn()?;
+    // Both pipes are serviced from threads, so a command that neither reads its input nor
+    // exits can still be timed out
+    if let Some(mut stdin) = child.stdin.take() {
+        let content = content.to_string();
+        std::thread::spawn(move || {
+            // A command that exits without reading stdin is not a delivery failure
+            let _ = stdin.write_all(content.as_bytes());
+        });
+    }
+    let (stderr_tx, stderr_rx) = mpsc::channel();
+    if let Some(mut stderr) = child.stderr.take() {
+        std::thread::spawn(move || {
+            let mut output = String::new();
+            let _ = stderr.read_to_string(&mut output);
+            let _ = stderr_tx.send(output);
+        });
+    }
+
+    let status = wait_with_timeout(&mut child)?;
+    if !status.success() {
+        // Anything the command left running in the background may keep stderr open
+        let stderr = stderr_rx
+            .recv_timeout(Duration::from_millis(500))
+            .unwrap_or_default();
+        let mut message = format!("Command failed ({})", status);
+        if !stderr.trim().is_empty() {
+            message.push_str(&format!(": {}", stderr.trim()));
+        }
+        return Err(AppError::Custom(message));
+    }
+    Ok(())
+}
+
+/// Waits for the child to exit, killing it once `DELIVERY_TIMEOUT` has passed.
+fn wait_with_timeout(child: &mut Child) -> Result<ExitStatus, AppError> {
+    let deadline = Instant::now() + DELIVERY_TIMEOUT;
+    loop {
+        if let Some(status) = child.try_wait()? {
+            return Ok(status);
+        }
+        if Instant::now() >= deadline {
+            let _ = child.kill();
+            let _ = child.wait();
+            return Err(timed_out());
+        }
+        std::thread::sleep(Duration::from_millis(20));
+    }
+}
+
+fn timed_out() -> AppError {
+    AppError::Custom(format!(
+        "Timed out after {} seconds",
+        DELIVERY_TIMEOUT.as_secs()
+    ))
+}
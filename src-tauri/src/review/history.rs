@@ -0,0 +1,117 @@
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use super::render::ExportFormat;
+use super::types::*;
+use crate::config::revu_dir;
+use crate::error::AppError;
+
+/// Metadata about one past export, stored alongside its comments under `~/.revu/history`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReviewMetadata {
+    pub id: String,
+    /// Host path of the repository root.
+    pub repository: String,
+    pub repo_name: String,
+    /// Milliseconds since the Unix epoch.
+    pub created_at: u64,
+    pub branch: Option<String>,
+    pub head: Option<String>,
+    pub format: ExportFormat,
+    pub summary: ReviewSummary,
+    /// Files the review was written to.
+    pub paths: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReviewRecord {
+    #[serde(flatten)]
+    pub metadata: ReviewMetadata,
+    pub comments: Vec<ReviewComment>,
+}
+
+fn history_dir() -> Result<PathBuf, AppError> {
+    Ok(revu_dir()?.join("history"))
+}
+
+fn record_path(id: &str) -> Result<PathBuf, AppError> {
+    // Ids are generated by revu; refuse anything that could escape the history directory
+    if id.is_empty() || id.contains(['/', '\\']) || id.starts_with('.') {
+        return Err(AppError::Custom(format!("Invalid review id: {}", id)));
+    }
+    Ok(history_dir()?.join(format!("{}.json", id)))
+}
+
+/// Stores a record of an export so it can be listed, reopened and re-exported later.
+pub fn save_record(record: &ReviewRecord) -> Result<(), AppError> {
+    fs::create_dir_all(history_dir()?)?;
+    let json = serde_json::to_string_pretty(record)
+        .map_err(|e| AppError::Custom(format!("Failed to serialise review: {}", e)))?;
+    fs::write(record_path(&record.metadata.id)?, json)?;
+    Ok(())
+}
+
+pub fn load_record(id: &str) -> Result<ReviewRecord, AppError> {
+    let content = fs::read_to_string(record_path(id)?)
+        .map_err(|_| AppError::Custom(format!("Review not found: {}", id)))?;
+    serde_json::from_str(&content)
+        .map_err(|e| AppError::Custom(format!("Corrupt review record {}: {}", id, e)))
+}
+
+/// Lists past exports of a repository, newest first.
+pub fn list_records(repository: &Path) -> Result<Vec<ReviewRecord>, AppError> {
+    let dir = history_dir()?;
+    let entries = match fs::read_dir(&dir) {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(e.into()),
+    };
+
+    let repository = repository.to_string_lossy();
+    let mut records: Vec<ReviewRecord> = entries
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| entry.path().extension().is_some_and(|ext| ext == "json"))
+        .filter_map(|entry| fs::read_to_string(entry.path()).ok())
+        .filter_map(|content| serde_json::from_str::<ReviewRecord>(&content).ok())
+        .filter(|record| record.metadata.repository == repository)
+        .collect();
+
+    records.sort_by_key(|r| std::cmp::Reverse(r.metadata.created_at));
+    Ok(records)
+}
+
+/// Deletes a repository's exports older than `max_age_ms` and/or beyond the newest `keep`,
+/// along with their files in `~/.revu`. Returns how many were removed.
+pub fn prune_records(
+    repository: &Path,
+    now_ms: u64,
+    max_age_ms: Option<u64>,
+    keep: Option<usize>,
+) -> Result<usize, AppError> {
+    let revu = revu_dir()?;
+    let mut removed = 0;
+
+    for (index, record) in list_records(repository)?.into_iter().enumerate() {
+        let too_old =
+            max_age_ms.is_some_and(|age| now_ms.saturating_sub(record.metadata.created_at) > age);
+        let over_count = keep.is_some_and(|keep| index >= keep);
+        if !too_old && !over_count {
+            continue;
+        }
+
+        // Only exports revu owns are deleted; files written into repositories are left alone
+        for path in &record.metadata.paths {
+            let path = Path::new(path);
+            if path.starts_with(&revu) && path.is_file() {
+                fs::remove_file(path)?;
+            }
+        }
+        fs::remove_file(record_path(&record.metadata.id)?)?;
+        removed += 1;
+    }
+
+    Ok(removed)
+}
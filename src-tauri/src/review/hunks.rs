@@ -0,0 +1,69 @@
+use super::types::*;
+use crate::error::AppError;
+use crate::git::{DiffHunk, FileDiff, GitRepository};
+
+/// Attaches to each comment the hunk enclosing its line range, from the staged or unstaged
+/// changes it was made on.
+pub fn attach_hunks(
+    review: &mut Review,
+    repo: &GitRepository,
+    context_lines: u32,
+) -> Result<(), AppError> {
+    for comment in &mut review.comments {
+        comment.hunk = repo
+            .find_hunk(
+                &comment.file_path,
+                comment.staged,
+                comment.is_old,
+                comment.start_line,
+                comment.end_line,
+                context_lines,
+            )?
+            .map(|hunk| trim_new_file_hunk(hunk, comment, context_lines));
+    }
+    Ok(())
+}
+
+/// Attaches hunks from diffs computed up front, for reviews without a repository (patches
+/// and directory comparisons). `context_lines` only trims hunks of newly added files.
+pub fn attach_hunks_from(review: &mut Review, files: &[FileDiff], context_lines: u32) {
+    for comment in &mut review.comments {
+        comment.hunk = files
+            .iter()
+            .find(|file| file.path == comment.file_path)
+            .and_then(|file| {
+                file.hunks
+                    .iter()
+                    .find(|hunk| hunk.covers(comment.is_old, comment.start_line, comment.end_line))
+            })
+            .map(|hunk| trim_new_file_hunk(hunk.clone(), comment, context_lines));
+    }
+}
+
+/// New files come back as one hunk spanning the whole file, so cut it down to the
+/// commented lines plus context.
+fn trim_new_file_hunk(hunk: DiffHunk, comment: &ReviewComment, context_lines: u32) -> DiffHunk {
+    if hunk.old_lines != 0 || comment.is_old {
+        return hunk;
+    }
+
+    let first = comment.start_line.saturating_sub(context_lines).max(1);
+    let last = comment.end_line.saturating_add(context_lines);
+    let lines: Vec<_> = hunk
+        .lines
+        .into_iter()
+        .filter(|l| l.new_line_no.is_some_and(|n| n >= first && n <= last))
+        .collect();
+    let new_start = lines.first().and_then(|l| l.new_line_no).unwrap_or(first);
+    let new_lines = lines.len() as u32;
+
+    DiffHunk {
+        header: format!("@@ -0,0 +{},{} @@", new_start, new_lines),
+        old_start: 0,
+        old_lines: 0,
+        new_start,
+        new_lines,
+        lines,
+        omitted_lines: 0,
+    }
+}
@@ -0,0 +1,19 @@
+pub mod delivery;
+pub mod history;
+pub mod hunks;
+pub mod patch_review;
+pub mod render;
+pub mod replies;
+pub mod staleness;
+pub mod store;
+pub mod suggestions;
+pub mod types;
+
+pub use delivery::{deliver_all, refused_report, DeliveryContext, ExportResult};
+pub use history::{ReviewMetadata, ReviewRecord};
+pub use hunks::{attach_hunks, attach_hunks_from};
+pub use patch_review::PatchReview;
+pub use render::{ExportFormat, ReviewRenderer};
+pub use staleness::check_freshness;
+pub use store::CommentStore;
+pub use types::*;
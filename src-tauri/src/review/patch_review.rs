@@ -0,0 +1,130 @@
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::config::revu_dir;
+use crate::error::AppError;
+use crate::git::{parse_patch, FileDiff, FileEntry, RepositoryStatus};
+
+const PATCH_FILE: &str = "patch.diff";
+const SESSION_FILE: &str = "session.json";
+
+/// A unified diff reviewed on its own, outside any repository (`git diff | revu -`).
+/// Each one gets a directory under `~/.revu/patches` holding the patch and its comments;
+/// that directory stands in for the repository path in the comment and export commands.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PatchReview {
+    pub id: String,
+    /// Where the patch came from: the file's name, or `stdin`.
+    pub name: String,
+    /// The session directory.
+    pub path: String,
+}
+
+fn patches_dir() -> Result<PathBuf, AppError> {
+    Ok(revu_dir()?.join("patches"))
+}
+
+impl PatchReview {
+    /// Starts (or resumes) reviewing a patch. The id is derived from the patch content, so
+    /// reviewing the same patch again picks up the comments left on it last time.
+    pub fn create(name: &str, patch: &str) -> Result<Self, AppError> {
+        if parse_patch(patch)?.is_empty() {
+            return Err(AppError::Custom("No changes found in patch".to_string()));
+        }
+
+        let id = format!(
+            "{}-{:016x}",
+            sanitize(name, "patch"),
+            fnv1a(patch.as_bytes())
+        );
+        let dir = patches_dir()?.join(&id);
+        fs::create_dir_all(&dir)?;
+        let review = PatchReview {
+            id,
+            name: name.to_string(),
+            path: dir.to_string_lossy().to_string(),
+        };
+        fs::write(dir.join(PATCH_FILE), patch)?;
+        let json = serde_json::to_string_pretty(&review)
+            .map_err(|e| AppError::Custom(format!("Failed to serialise patch review: {}", e)))?;
+        fs::write(dir.join(SESSION_FILE), json)?;
+        Ok(review)
+    }
+
+    /// Opens the patch review at `path`, or returns `None` if it isn't one.
+    pub fn open(path: &Path) -> Result<Option<Self>, AppError> {
+        if !path.starts_with(patches_dir()?) {
+            return Ok(None);
+        }
+        match fs::read_to_string(path.join(SESSION_FILE)) {
+            Ok(content) => serde_json::from_str(&content).map(Some).map_err(|e| {
+                AppError::Custom(format!("Corrupt patch review {}: {}", path.display(), e))
+            }),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    pub fn dir(&self) -> &Path {
+        Path::new(&self.path)
+    }
+
+    pub fn patch(&self) -> Result<String, AppError> {
+        Ok(fs::read_to_string(self.dir().join(PATCH_FILE))?)
+    }
+
+    pub fn files(&self) -> Result<Vec<FileDiff>, AppError> {
+        parse_patch(&self.patch()?)
+    }
+
+    /// Lists the patch's files as unstaged changes.
+    pub fn get_status(&self) -> Result<RepositoryStatus, AppError> {
+        let files: Vec<FileEntry> = self
+            .files()?
+            .into_iter()
+            .map(|diff| FileEntry {
+                path: diff.path,
+                status: diff.status,
+                staged: false,
+                old_path: diff.old_path,
+            })
+            .collect();
+        Ok(RepositoryStatus {
+            path: self.path.clone(),
+            branch: None,
+            staged_count: 0,
+            unstaged_count: files.len(),
+            files,
+        })
+    }
+}
+
+/// Keeps a name usable as part of a file or directory name, using `fallback` when nothing
+/// usable is left.
+pub(crate) fn sanitize(name: &str, fallback: &str) -> String {
+    let cleaned: String = name
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '-' || c == '_' {
+                c
+            } else {
+                '-'
+            }
+        })
+        .collect();
+    let cleaned = cleaned.trim_matches('-');
+    if cleaned.is_empty() {
+        fallback.to_string()
+    } else {
+        cleaned.to_string()
+    }
+}
+
+/// A stable content hash (unlike `DefaultHasher`, which may change between Rust releases).
+pub(crate) fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf29ce484222325, |hash, &b| {
+        (hash ^ b as u64).wrapping_mul(0x100000001b3)
+    })
+}
@@ -0,0 +1,43 @@
+use serde::Serialize;
+
+use super::ReviewRenderer;
+use crate::review::types::*;
+
+/// Bumped whenever the shape of the JSON document changes incompatibly.
+pub const JSON_FORMAT_VERSION: u32 = 1;
+
+/// A versioned JSON document for tools that parse reviews programmatically.
+pub struct JsonRenderer;
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct JsonReview<'a> {
+    version: u32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    id: Option<&'a str>,
+    /// Where the agent should write its reply file.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    reply_path: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    repository: Option<&'a str>,
+    summary: ReviewSummary,
+    comments: &'a [ReviewComment],
+}
+
+impl ReviewRenderer for JsonRenderer {
+    fn render(&self, review: &Review) -> String {
+        let document = JsonReview {
+            version: JSON_FORMAT_VERSION,
+            id: review.id.as_deref(),
+            reply_path: review.reply_path.as_deref(),
+            repository: review.repository.as_deref(),
+            summary: review.summary(),
+            comments: &review.comments,
+        };
+        serde_json::to_string_pretty(&document).unwrap_or_default()
+    }
+
+    fn extension(&self) -> &'static str {
+        "json"
+    }
+}
@@ -0,0 +1,162 @@
+use super::{plural, push_hunk, reply_instructions, ReviewRenderer};
+use crate::git::detect_language;
+use crate::review::types::*;
+
+/// Human-readable Markdown, suitable for pasting into a chat.
+pub struct MarkdownRenderer;
+
+impl ReviewRenderer for MarkdownRenderer {
+    fn render(&self, review: &Review) -> String {
+        if review.is_empty() {
+            return String::new();
+        }
+
+        let summary = review.summary();
+        let mut out = String::from("# Code Review\n\n");
+        if let Some(repository) = &review.repository {
+            out.push_str(&format!("Repository: `{}`\n\n", repository));
+        }
+        out.push_str(&format!(
+            "{} comment{}, {} requiring action ({} issue{}, {} suggestion{}), {} question{}.\n",
+            summary.total,
+            plural(summary.total),
+            summary.action_required,
+            summary.issues,
+            plural(summary.issues),
+            summary.suggestions,
+            plural(summary.suggestions),
+            summary.questions,
+            plural(summary.questions)
+        ));
+
+        let mut current_file: Option<&str> = None;
+        for comment in &review.comments {
+            if current_file != Some(comment.file_path.as_str()) {
+                out.push_str(&format!("\n## `{}`\n", comment.file_path));
+                current_file = Some(&comment.file_path);
+            }
+
+            out.push_str(&format!(
+                "\n### {} — line {} ({})\n\n",
+                capitalize(comment.category.as_str()),
+                comment.line_ref(),
+                comment.side()
+            ));
+
+            if let Some(note) = comment.freshness_note() {
+                out.push_str(&format!("> **Note:** {}\n\n", note));
+            }
+
+            if !comment.code_snippet.is_empty() {
+                let fence = fence_for(&comment.code_snippet);
+                out.push_str(&fence);
+                out.push_str(&detect_language(&comment.file_path).unwrap_or_default());
+                out.push('\n');
+                out.push_str(&comment.code_snippet);
+                if !comment.code_snippet.ends_with('\n') {
+                    out.push('\n');
+                }
+                out.push_str(&fence);
+                out.push_str("\n\n");
+            }
+
+            if let Some(suggestion) = &comment.suggestion {
+                let fence = fence_for(suggestion);
+                out.push_str(&format!("{}suggestion\n{}", fence, suggestion));
+                if !suggestion.ends_with('\n') {
+                    out.push('\n');
+                }
+                out.push_str(&fence);
+                out.push_str("\n\n");
+            }
+
+            if let Some(hunk) = &comment.hunk {
+                let mut diff = String::new();
+                push_hunk(&mut diff, hunk);
+                let fence = fence_for(&diff);
+                out.push_str(&format!("{}diff\n{}{}\n\n", fence, diff, fence));
+            }
+
+            out.push_str(comment.content.trim_end());
+            out.push('\n');
+            for reply in &comment.replies {
+                let author = capitalize(reply.author.as_str());
+                let heading = match reply.status {
+                    Some(status) => format!("**{}** ({})", author, status.as_str()),
+                    None => format!("**{}**", author),
+                };
+                out.push_str(&format!("\n> {}: ", heading));
+                out.push_str(&reply.content.trim_end().replace('\n', "\n> "));
+                out.push('\n');
+            }
+            out.push_str(&format!("\n<sub>Comment id: `{}`</sub>\n", comment.id));
+        }
+
+        if let Some(reply_path) = &review.reply_path {
+            out.push_str("\n---\n\n");
+            out.push_str(&reply_instructions(reply_path));
+        }
+
+        out
+    }
+
+    fn extension(&self) -> &'static str {
+        "md"
+    }
+}
+
+fn capitalize(s: &str) -> String {
+    let mut chars = s.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
+
+/// Picks a backtick fence longer than any backtick run inside the snippet.
+fn fence_for(code: &str) -> String {
+    let mut longest = 0;
+    let mut run = 0;
+    for c in code.chars() {
+        if c == '`' {
+            run += 1;
+            longest = longest.max(run);
+        } else {
+            run = 0;
+        }
+    }
+    "`".repeat((longest + 1).max(3))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fence_outlasts_backtick_runs() {
+        assert_eq!(fence_for("let a = 1;"), "```");
+        assert_eq!(fence_for("`a` and ``b``"), "```");
+        assert_eq!(fence_for("```rust\n```"), "````");
+        assert_eq!(fence_for("x ````` y"), "``````");
+    }
+
+    #[test]
+    fn snippet_with_a_fence_stays_in_its_block() {
+        let comment: ReviewComment = serde_json::from_value(serde_json::json!({
+            "id": "c1",
+            "filePath": "README.md",
+            "startLine": 1,
+            "endLine": 3,
+            "content": "Check this",
+            "category": "question",
+            "codeSnippet": "```sh\nmake\n```",
+            "suggestion": "````\nx\n````\n",
+            "isOld": false,
+        }))
+        .unwrap();
+        let out = MarkdownRenderer.render(&Review::new(vec![comment]));
+
+        assert!(out.contains("\n````markdown\n```sh\nmake\n```\n````\n"));
+        assert!(out.contains("\n`````suggestion\n````\nx\n````\n`````\n"));
+    }
+}
@@ -0,0 +1,95 @@
+pub mod json;
+pub mod markdown;
+pub mod patch;
+pub mod sarif;
+pub mod xml;
+
+use serde::{Deserialize, Serialize};
+
+use super::types::Review;
+use crate::git::{DiffHunk, DiffLine, LineType};
+
+pub use json::JsonRenderer;
+pub use markdown::MarkdownRenderer;
+pub use patch::PatchRenderer;
+pub use sarif::SarifRenderer;
+pub use xml::{escape_xml, XmlRenderer};
+
+/// Turns a review into one concrete output document.
+pub trait ReviewRenderer {
+    fn render(&self, review: &Review) -> String;
+
+    /// File extension (without the dot) used when the rendered review is written to disk.
+    fn extension(&self) -> &'static str;
+
+    /// Whether the renderer needs `Review::files` populated with the current diffs.
+    fn needs_diffs(&self) -> bool {
+        false
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum ExportFormat {
+    #[default]
+    Xml,
+    Json,
+    Markdown,
+    Sarif,
+    Patch,
+}
+
+impl ExportFormat {
+    pub fn renderer(&self) -> Box<dyn ReviewRenderer + Send + Sync> {
+        match self {
+            ExportFormat::Xml => Box::new(XmlRenderer),
+            ExportFormat::Json => Box::new(JsonRenderer),
+            ExportFormat::Markdown => Box::new(MarkdownRenderer),
+            ExportFormat::Sarif => Box::new(SarifRenderer),
+            ExportFormat::Patch => Box::new(PatchRenderer),
+        }
+    }
+}
+
+/// Tells the agent how to report back on each comment.
+pub(crate) fn reply_instructions(reply_path: &str) -> String {
+    format!(
+        "When you have processed the comments, write your reply as JSON to {} in the form \
+         {{\"replies\": [{{\"commentId\": \"COMMENT_ID\", \"status\": \"addressed|disputed|answered\", \"message\": \"what you did, or why not\"}}]}}.\n",
+        reply_path
+    )
+}
+
+pub(crate) fn plural(n: usize) -> &'static str {
+    if n != 1 {
+        "s"
+    } else {
+        ""
+    }
+}
+
+/// Appends a hunk in unified diff form: its header followed by `+`/`-`/` ` prefixed lines.
+pub(crate) fn push_hunk(out: &mut String, hunk: &DiffHunk) {
+    out.push_str(&hunk.header);
+    out.push('\n');
+    for line in &hunk.lines {
+        push_diff_line(out, line);
+    }
+}
+
+pub(crate) fn push_diff_line(out: &mut String, line: &DiffLine) {
+    let marker = match line.line_type {
+        LineType::Addition => '+',
+        LineType::Deletion => '-',
+        LineType::Context => ' ',
+        LineType::Header => return,
+    };
+    out.push(marker);
+    out.push_str(&line.content);
+    if !line.content.ends_with('\n') {
+        out.push('\n');
+    }
+    if line.no_newline {
+        out.push_str("\\ No newline at end of file\n");
+    }
+}
@@ -0,0 +1,213 @@
+use std::collections::HashSet;
+
+use super::{push_hunk, ReviewRenderer};
+use crate::git::{FileDiff, FileStatus};
+use crate::review::types::*;
+
+/// A unified patch of the reviewed changes with each file's comments listed after its hunks.
+/// `git apply` skips lines after a file's last hunk, so the output still applies; a comment
+/// line inside or between hunks would break the hunk line counts.
+pub struct PatchRenderer;
+
+impl ReviewRenderer for PatchRenderer {
+    fn render(&self, review: &Review) -> String {
+        let mut out = String::new();
+        let mut placed: HashSet<&str> = HashSet::new();
+
+        for file in &review.files {
+            write_file_header(&mut out, file);
+            for hunk in &file.hunks {
+                push_hunk(&mut out, hunk);
+            }
+
+            for comment in review.comments.iter().filter(|c| c.file_path == file.path) {
+                if placed.insert(comment.id.as_str()) {
+                    write_comment(&mut out, comment);
+                }
+            }
+        }
+
+        let unplaced: Vec<&ReviewComment> = review
+            .comments
+            .iter()
+            .filter(|c| !placed.contains(c.id.as_str()))
+            .collect();
+        if !unplaced.is_empty() {
+            out.push_str("# revu: comments on files without changes\n");
+            for comment in unplaced {
+                out.push_str(&format!("# {}\n", comment.file_path));
+                write_comment(&mut out, comment);
+            }
+        }
+
+        out
+    }
+
+    fn extension(&self) -> &'static str {
+        "patch"
+    }
+
+    fn needs_diffs(&self) -> bool {
+        true
+    }
+}
+
+fn write_file_header(out: &mut String, file: &FileDiff) {
+    let old_path = file.old_path.as_deref().unwrap_or(&file.path);
+    out.push_str(&format!("diff --git a/{} b/{}\n", old_path, file.path));
+    match (file.old_mode, file.new_mode) {
+        (Some(old), Some(new)) if old != new => {
+            out.push_str(&format!("old mode {:o}\nnew mode {:o}\n", old, new));
+        }
+        (None, Some(new)) if file.status != FileStatus::Modified => {
+            out.push_str(&format!("new file mode {:o}\n", new));
+        }
+        (Some(old), None) if file.status == FileStatus::Deleted => {
+            out.push_str(&format!("deleted file mode {:o}\n", old));
+        }
+        _ => {}
+    }
+
+    if file.is_binary {
+        out.push_str(&format!(
+            "Binary files a/{} and b/{} differ\n",
+            old_path, file.path
+        ));
+        return;
+    }
+
+    let old_name = match file.status {
+        FileStatus::Added | FileStatus::Untracked => "/dev/null".to_string(),
+        _ => format!("a/{}", old_path),
+    };
+    let new_name = match file.status {
+        FileStatus::Deleted => "/dev/null".to_string(),
+        _ => format!("b/{}", file.path),
+    };
+    out.push_str(&format!("--- {}\n+++ {}\n", old_name, new_name));
+}
+
+fn write_comment(out: &mut String, comment: &ReviewComment) {
+    let mut lines = comment.content.lines();
+    out.push_str(&format!(
+        "# revu {} ({} line {}): {}\n",
+        comment.category.as_str(),
+        comment.side(),
+        comment.line_ref(),
+        lines.next().unwrap_or_default()
+    ));
+    for line in lines {
+        out.push_str(&format!("#   {}\n", line));
+    }
+    if let Some(note) = comment.freshness_note() {
+        out.push_str(&format!("#   ({})\n", note));
+    }
+    if let Some(suggestion) = &comment.suggestion {
+        out.push_str("#   suggested replacement:\n");
+        for line in suggestion.lines() {
+            out.push_str(&format!("#   | {}\n", line));
+        }
+    }
+    for reply in &comment.replies {
+        let mut lines = reply.content.lines();
+        let status = reply
+            .status
+            .map(|s| format!(" ({})", s.as_str()))
+            .unwrap_or_default();
+        out.push_str(&format!(
+            "#   > {}{}: {}\n",
+            reply.author.as_str(),
+            status,
+            lines.next().unwrap_or_default()
+        ));
+        for line in lines {
+            out.push_str(&format!("#   >   {}\n", line));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::git::parse_patch;
+
+    const PATCH: &str = "diff --git a/a.txt b/a.txt
+--- a/a.txt
++++ b/a.txt
+@@ -1,2 +1,2 @@
+ one
+-two
++TWO
+@@ -10,2 +10,3 @@
+ ten
+ eleven
++twelve
+diff --git a/b.txt b/b.txt
+--- a/b.txt
++++ b/b.txt
+@@ -1 +1 @@
+-b
++B
+";
+
+    fn comment(id: &str, file_path: &str, line: u32, content: &str) -> ReviewComment {
+        serde_json::from_value(serde_json::json!({
+            "id": id,
+            "filePath": file_path,
+            "startLine": line,
+            "endLine": line,
+            "content": content,
+            "category": "issue",
+            "isOld": false,
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn comments_follow_each_files_hunks() {
+        let review = Review::new(vec![
+            comment("c1", "a.txt", 2, "First"),
+            comment("c2", "a.txt", 12, "Second\nmore"),
+            comment("c3", "b.txt", 1, "Third"),
+            comment("c4", "c.txt", 5, "Elsewhere"),
+        ])
+        .with_files(parse_patch(PATCH).unwrap());
+        let out = PatchRenderer.render(&review);
+
+        let (a, rest) = out.split_once("diff --git a/b.txt").unwrap();
+        assert!(a.ends_with(
+            "+twelve\n\
+             # revu issue (new line 2): First\n\
+             # revu issue (new line 12): Second\n\
+             #   more\n"
+        ));
+        assert!(rest.contains(
+            "+B\n\
+             # revu issue (new line 1): Third\n\
+             # revu: comments on files without changes\n\
+             # c.txt\n\
+             # revu issue (new line 5): Elsewhere\n"
+        ));
+    }
+
+    #[test]
+    fn output_parses_back_to_the_same_changes() {
+        let files = parse_patch(PATCH).unwrap();
+        let review = Review::new(vec![
+            comment("c1", "a.txt", 2, "First"),
+            comment("c2", "b.txt", 1, "Second"),
+        ])
+        .with_files(files.clone());
+        let reparsed = parse_patch(&PatchRenderer.render(&review)).unwrap();
+
+        assert_eq!(reparsed.len(), files.len());
+        for (before, after) in files.iter().zip(&reparsed) {
+            assert_eq!(after.path, before.path);
+            let lines = |file: &FileDiff| -> Vec<(String, usize)> {
+                let hunks = file.hunks.iter();
+                hunks.map(|h| (h.header.clone(), h.lines.len())).collect()
+            };
+            assert_eq!(lines(after), lines(before));
+        }
+    }
+}
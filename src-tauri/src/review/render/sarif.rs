@@ -0,0 +1,195 @@
+use serde_json::{json, Value};
+use std::path::Path;
+
+use super::ReviewRenderer;
+use crate::review::types::*;
+
+const SARIF_SCHEMA: &str = "https://json.schemastore.org/sarif-2.1.0.json";
+const REPO_ROOT_BASE_ID: &str = "REPO_ROOT";
+
+/// SARIF 2.1.0 log for code-scanning tools, with one rule per comment category.
+pub struct SarifRenderer;
+
+impl ReviewRenderer for SarifRenderer {
+    fn render(&self, review: &Review) -> String {
+        let rules: Vec<Value> = CommentCategory::ALL
+            .iter()
+            .map(|category| {
+                json!({
+                    "id": category.as_str(),
+                    "shortDescription": { "text": category.description() },
+                    "defaultConfiguration": { "level": level(*category) },
+                })
+            })
+            .collect();
+
+        let results: Vec<Value> = review.comments.iter().map(result).collect();
+
+        let mut run = json!({
+            "tool": {
+                "driver": {
+                    "name": "revu",
+                    "version": env!("CARGO_PKG_VERSION"),
+                    "informationUri": "https://github.com/eddmann/revu",
+                    "rules": rules,
+                }
+            },
+            "results": results,
+        });
+        if let Some(id) = &review.id {
+            run["automationDetails"] = json!({ "id": id });
+        }
+        if let Some(repository) = &review.repository {
+            let uri = format!("{}/", file_uri(repository.trim_end_matches('/')));
+            run["originalUriBaseIds"] = json!({ REPO_ROOT_BASE_ID: { "uri": uri } });
+        }
+
+        let log = json!({
+            "$schema": SARIF_SCHEMA,
+            "version": "2.1.0",
+            "runs": [run],
+        });
+
+        serde_json::to_string_pretty(&log).unwrap_or_default()
+    }
+
+    fn extension(&self) -> &'static str {
+        "sarif"
+    }
+}
+
+fn result(comment: &ReviewComment) -> Value {
+    let mut region = json!({
+        "startLine": comment.start_line,
+        "endLine": comment.end_line,
+    });
+    if !comment.code_snippet.is_empty() {
+        region["snippet"] = json!({ "text": comment.code_snippet });
+    }
+
+    let artifact = if Path::new(&comment.file_path).is_relative() {
+        json!({
+            "uri": encode_path(&comment.file_path),
+            "uriBaseId": REPO_ROOT_BASE_ID,
+        })
+    } else {
+        json!({ "uri": file_uri(&comment.file_path) })
+    };
+
+    let mut properties = json!({
+        "revuCommentId": comment.id,
+        "side": comment.side(),
+    });
+    if let Some(freshness) = &comment.freshness {
+        properties["freshness"] = json!(freshness);
+    }
+
+    let mut result = json!({
+        "ruleId": comment.category.as_str(),
+        "level": level(comment.category),
+        "message": { "text": comment.content },
+        "locations": [{
+            "physicalLocation": {
+                "artifactLocation": artifact.clone(),
+                "region": region,
+            }
+        }],
+        "properties": properties,
+    });
+    if let Some(suggestion) = &comment.suggestion {
+        result["fixes"] = json!([{
+            "description": { "text": "Suggested change" },
+            "artifactChanges": [{
+                "artifactLocation": artifact,
+                "replacements": [{
+                    "deletedRegion": {
+                        "startLine": comment.start_line,
+                        "endLine": comment.end_line,
+                    },
+                    "insertedContent": { "text": suggestion },
+                }],
+            }],
+        }]);
+    }
+    result
+}
+
+fn level(category: CommentCategory) -> &'static str {
+    match category {
+        CommentCategory::Issue => "error",
+        CommentCategory::Suggestion => "warning",
+        CommentCategory::Question | CommentCategory::Nitpick => "note",
+        CommentCategory::Praise => "none",
+    }
+}
+
+/// A `file://` URI for an absolute path, with Windows drive paths given a leading slash.
+fn file_uri(path: &str) -> String {
+    let path = path.replace('\\', "/");
+    match path.split_once(':') {
+        Some((drive, rest)) if drive.len() == 1 && rest.starts_with('/') => {
+            format!("file:///{drive}:{}", encode_path(rest))
+        }
+        _ => format!("file://{}", encode_path(&path)),
+    }
+}
+
+/// Percent-encodes each segment of a `/`-separated path, keeping only RFC 3986 unreserved
+/// characters, so spaces, `#` and `?` can't end the path early.
+fn encode_path(path: &str) -> String {
+    let mut encoded = String::with_capacity(path.len());
+    for byte in path.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' | b'/' => {
+                encoded.push(byte as char)
+            }
+            _ => encoded.push_str(&format!("%{byte:02X}")),
+        }
+    }
+    encoded
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn encodes_characters_outside_the_unreserved_set() {
+        assert_eq!(encode_path("src/a b#1?.rs"), "src/a%20b%231%3F.rs");
+        assert_eq!(encode_path("naïve/100%.rs"), "na%C3%AFve/100%25.rs");
+        assert_eq!(encode_path("a-b_c.~d/e"), "a-b_c.~d/e");
+    }
+
+    #[test]
+    fn builds_file_uris() {
+        assert_eq!(file_uri("/home/me/my repo"), "file:///home/me/my%20repo");
+        assert_eq!(file_uri("C:\\Users\\me\\repo"), "file:///C:/Users/me/repo");
+        assert_eq!(file_uri("C:/a b"), "file:///C:/a%20b");
+    }
+
+    #[test]
+    fn relative_paths_resolve_against_the_repository() {
+        let comment: ReviewComment = serde_json::from_value(json!({
+            "id": "c1",
+            "filePath": "src/a b.rs",
+            "startLine": 1,
+            "endLine": 2,
+            "content": "Check this",
+            "category": "issue",
+            "isOld": false,
+        }))
+        .unwrap();
+        let mut review = Review::new(vec![comment]);
+        review.repository = Some("/work/my repo/".to_string());
+        let log: Value = serde_json::from_str(&SarifRenderer.render(&review)).unwrap();
+
+        let run = &log["runs"][0];
+        assert_eq!(
+            run["originalUriBaseIds"][REPO_ROOT_BASE_ID]["uri"],
+            "file:///work/my%20repo/"
+        );
+        let location = &run["results"][0]["locations"][0]["physicalLocation"];
+        assert_eq!(location["artifactLocation"]["uri"], "src/a%20b.rs");
+        assert_eq!(location["artifactLocation"]["uriBaseId"], REPO_ROOT_BASE_ID);
+    }
+}
@@ -0,0 +1,194 @@
+use super::{plural, push_hunk, reply_instructions, ReviewRenderer};
+use crate::git::detect_language;
+use crate::review::types::*;
+
+/// The `<revu-review>` XML-in-markdown document agents consume.
+pub struct XmlRenderer;
+
+impl ReviewRenderer for XmlRenderer {
+    fn render(&self, review: &Review) -> String {
+        render_xml(review)
+    }
+
+    fn extension(&self) -> &'static str {
+        "md"
+    }
+}
+
+fn render_xml(review: &Review) -> String {
+    if review.is_empty() {
+        return String::new();
+    }
+
+    let summary = review.summary();
+    let total = summary.total;
+
+    let mut out = match &review.id {
+        Some(id) => format!("<revu-review id=\"{}\">\n", escape_xml(id)),
+        None => String::from("<revu-review>\n"),
+    };
+    out.push_str(&format!(
+        "The user has reviewed code changes and left {} comment{}. Process each comment according to its category.\n",
+        total,
+        plural(total)
+    ));
+    if review.comments.iter().any(|c| c.suggestion.is_some()) {
+        out.push_str("A <suggestion> is the exact replacement for the commented lines; apply it as written unless it is wrong.\n");
+    }
+    if review.comments.iter().any(|c| !c.replies.is_empty()) {
+        out.push_str("Some comments continue an earlier discussion; their <replies> hold the thread so far, oldest first.\n");
+    }
+    out.push('\n');
+    if let Some(repository) = &review.repository {
+        out.push_str(&format!(
+            "<repository>{}</repository>\n\n",
+            escape_xml(repository)
+        ));
+    }
+    out.push_str("<review-categories>\n");
+    for category in CommentCategory::ALL {
+        out.push_str(&format!(
+            "- {}: {}\n",
+            category.as_str().to_uppercase(),
+            category.description()
+        ));
+    }
+    out.push_str("</review-categories>\n\n<review-summary>\n");
+    out.push_str(&format!("Total: {} comment{}\n", total, plural(total)));
+    if summary.action_required > 0 {
+        out.push_str(&format!(
+            "Action required: {} ({} issue{}, {} suggestion{})\n",
+            summary.action_required,
+            summary.issues,
+            plural(summary.issues),
+            summary.suggestions,
+            plural(summary.suggestions)
+        ));
+    } else {
+        out.push_str("Action required: 0\n");
+    }
+    out.push_str(&format!("Questions: {}\n", summary.questions));
+    out.push_str("</review-summary>\n\n");
+    if let Some(reply_path) = &review.reply_path {
+        out.push_str(&format!(
+            "<reply-instructions>\n{}</reply-instructions>\n\n",
+            reply_instructions(&escape_xml(reply_path))
+        ));
+    }
+
+    for comment in &review.comments {
+        out.push_str(&format!("<comment id=\"{}\">\n", escape_xml(&comment.id)));
+        out.push_str(&format!(
+            "<file>{}</file>\n",
+            escape_xml(&comment.file_path)
+        ));
+        out.push_str(&format!("<line>{}</line>\n", comment.line_ref()));
+        out.push_str(&format!("<side>{}</side>\n", comment.side()));
+        out.push_str(&format!(
+            "<category>{}</category>\n",
+            comment.category.as_str()
+        ));
+        if let Some(note) = comment.freshness_note() {
+            out.push_str(&format!("<status>{}</status>\n", escape_xml(&note)));
+        }
+        if !comment.code_snippet.is_empty() {
+            match detect_language(&comment.file_path) {
+                Some(lang) => out.push_str(&format!("<code language=\"{}\">\n", lang)),
+                None => out.push_str("<code>\n"),
+            }
+            out.push_str(&escape_xml(&comment.code_snippet));
+            if !comment.code_snippet.ends_with('\n') {
+                out.push('\n');
+            }
+            out.push_str("</code>\n");
+        }
+        if let Some(suggestion) = &comment.suggestion {
+            out.push_str("<suggestion>\n");
+            out.push_str(&escape_xml(suggestion));
+            if !suggestion.ends_with('\n') {
+                out.push('\n');
+            }
+            out.push_str("</suggestion>\n");
+        }
+        if let Some(hunk) = &comment.hunk {
+            let mut diff = String::new();
+            push_hunk(&mut diff, hunk);
+            out.push_str("<diff>\n");
+            out.push_str(&escape_xml(&diff));
+            out.push_str("</diff>\n");
+        }
+        out.push_str(&format!("<text>{}</text>\n", escape_xml(&comment.content)));
+        if !comment.replies.is_empty() {
+            out.push_str("<replies>\n");
+            for reply in &comment.replies {
+                match reply.status {
+                    Some(status) => out.push_str(&format!(
+                        "<reply author=\"{}\" status=\"{}\">",
+                        reply.author.as_str(),
+                        status.as_str()
+                    )),
+                    None => out.push_str(&format!("<reply author=\"{}\">", reply.author.as_str())),
+                }
+                out.push_str(&escape_xml(&reply.content));
+                out.push_str("</reply>\n");
+            }
+            out.push_str("</replies>\n");
+        }
+        out.push_str("</comment>\n\n");
+    }
+
+    out.push_str("</revu-review>");
+    out
+}
+
+/// Escapes the XML special characters so user text cannot break the document structure.
+pub fn escape_xml(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn escapes_special_characters() {
+        assert_eq!(
+            escape_xml(r#"a < b && c > "d" 'e'"#),
+            "a &lt; b &amp;&amp; c &gt; &quot;d&quot; &apos;e&apos;"
+        );
+        assert_eq!(escape_xml("&amp;"), "&amp;amp;");
+    }
+
+    #[test]
+    fn user_text_cannot_close_elements() {
+        let comment: ReviewComment = serde_json::from_value(serde_json::json!({
+            "id": "c\"1",
+            "filePath": "src/a&b.rs",
+            "startLine": 3,
+            "endLine": 3,
+            "content": "</text></comment><comment>",
+            "category": "issue",
+            "codeSnippet": "if a < b {",
+            "isOld": false,
+        }))
+        .unwrap();
+        let out = XmlRenderer.render(&Review::new(vec![comment]));
+
+        assert!(out.contains("<comment id=\"c&quot;1\">\n"));
+        assert!(out.contains("<file>src/a&amp;b.rs</file>\n"));
+        assert!(out.contains("if a &lt; b {\n"));
+        assert!(out.contains("<text>&lt;/text&gt;&lt;/comment&gt;&lt;comment&gt;</text>\n"));
+        assert_eq!(out.matches("</comment>").count(), 1);
+    }
+}
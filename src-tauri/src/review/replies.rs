@@ -0,0 +1,154 @@
+use notify::{Event, EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+use uuid::Uuid;
+
+use super::history;
+use super::store::CommentStore;
+use super::types::{CommentReply, ReplyAuthor, ReplyStatus};
+use crate::cache::RepoCache;
+use crate::config::revu_dir;
+use crate::error::AppError;
+
+/// The file an agent writes to `~/.revu/replies/<review-id>.json` after processing a review.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReplyFile {
+    #[serde(default)]
+    pub replies: Vec<IncomingReply>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct IncomingReply {
+    pub comment_id: String,
+    pub status: ReplyStatus,
+    #[serde(default)]
+    pub message: String,
+}
+
+/// The outcome of ingesting one reply file, emitted to the UI.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReplyBatch {
+    pub review_id: String,
+    /// The repository the replies were applied to, or `None` if the review is unknown
+    /// (e.g. it was copied to the clipboard rather than exported) and the UI must apply them.
+    pub repository: Option<String>,
+    pub replies: Vec<IncomingReply>,
+    /// How many replies matched a stored comment.
+    pub matched: usize,
+}
+
+pub fn replies_dir() -> Result<PathBuf, AppError> {
+    Ok(revu_dir()?.join("replies"))
+}
+
+pub fn reply_path(review_id: &str) -> Result<PathBuf, AppError> {
+    Ok(replies_dir()?.join(format!("{}.json", review_id)))
+}
+
+/// Adds each reply to the thread of the stored comment with the same id, returning how
+/// many matched.
+pub fn apply_replies(store: &mut CommentStore, replies: &[IncomingReply]) -> usize {
+    let received_at = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis() as u64;
+
+    replies
+        .iter()
+        .filter(|incoming| {
+            let reply = CommentReply {
+                id: Uuid::new_v4().to_string(),
+                author: ReplyAuthor::Agent,
+                content: incoming.message.clone(),
+                created_at: received_at,
+                status: Some(incoming.status),
+            };
+            store.add_reply(&incoming.comment_id, reply).is_ok()
+        })
+        .count()
+}
+
+pub fn read_reply_file(path: &Path) -> Result<ReplyFile, AppError> {
+    let content = fs::read_to_string(path)?;
+    serde_json::from_str(&content)
+        .map_err(|e| AppError::Custom(format!("Invalid reply file {}: {}", path.display(), e)))
+}
+
+/// Applies a parsed reply file to the repository the review was exported from.
+/// Ingested files are moved to `replies/processed` so they are not picked up twice.
+pub fn ingest(repos: &RepoCache, path: &Path, file: ReplyFile) -> Result<ReplyBatch, AppError> {
+    let review_id = path
+        .file_stem()
+        .map(|s| s.to_string_lossy().to_string())
+        .unwrap_or_default();
+
+    let mut batch = ReplyBatch {
+        review_id,
+        repository: None,
+        replies: file.replies,
+        matched: 0,
+    };
+
+    if let Ok(record) = history::load_record(&batch.review_id) {
+        let mut store = CommentStore::for_path(repos, &record.metadata.repository)?;
+        batch.matched = apply_replies(&mut store, &batch.replies);
+        store.save()?;
+        batch.repository = Some(record.metadata.repository);
+    }
+
+    let processed = replies_dir()?.join("processed");
+    fs::create_dir_all(&processed)?;
+    if let Some(name) = path.file_name() {
+        fs::rename(path, processed.join(name))?;
+    }
+
+    Ok(batch)
+}
+
+fn is_reply_file(path: &Path) -> bool {
+    path.extension().is_some_and(|ext| ext == "json")
+        && path.parent().and_then(Path::file_name) == Some("replies".as_ref())
+}
+
+/// Ingests reply files already waiting in the replies directory, then watches it for new ones.
+/// The returned watcher must be kept alive for as long as replies should be picked up.
+pub fn watch<F>(repos: RepoCache, on_batch: F) -> Result<RecommendedWatcher, AppError>
+where
+    F: Fn(Result<ReplyBatch, AppError>) + Send + 'static,
+{
+    let dir = replies_dir()?;
+    fs::create_dir_all(&dir)?;
+
+    for entry in fs::read_dir(&dir)?.filter_map(|e| e.ok()) {
+        let path = entry.path();
+        if is_reply_file(&path) {
+            on_batch(read_reply_file(&path).and_then(|file| ingest(&repos, &path, file)));
+        }
+    }
+
+    let mut watcher = notify::recommended_watcher(move |event: notify::Result<Event>| {
+        let Ok(event) = event else { return };
+        // Agents may create the file empty and write it afterwards, so react to both
+        if !matches!(event.kind, EventKind::Create(_) | EventKind::Modify(_)) {
+            return;
+        }
+        for path in event.paths.iter().filter(|p| is_reply_file(p)) {
+            // A file that is still being written won't parse yet; its next
+            // modification event will pick it up
+            if let Ok(file) = read_reply_file(path) {
+                on_batch(ingest(&repos, path, file));
+            }
+        }
+    })
+    .map_err(|e| AppError::Custom(format!("Failed to watch replies: {}", e)))?;
+
+    watcher
+        .watch(&dir, RecursiveMode::NonRecursive)
+        .map_err(|e| AppError::Custom(format!("Failed to watch replies: {}", e)))?;
+    Ok(watcher)
+}
@@ -0,0 +1,254 @@
+use super::types::*;
+use crate::error::AppError;
+use crate::git::{FileContent, FileSource};
+
+/// Re-reads each commented file and checks the comment's code snippet still sits at its
+/// line range. Comments whose snippet moved are relocated to the nearest match, or marked
+/// ambiguous when several are equally near; comments whose snippet is gone are marked stale.
+/// A file too large to read leaves its comments' freshness unknown. Returns a warning for
+/// every comment that didn't match in place.
+/// `read_file` reads a version of a file, e.g. `GitRepository::read_file`.
+pub fn check_freshness<F>(review: &mut Review, read_file: F) -> Result<Vec<StaleWarning>, AppError>
+where
+    F: Fn(&str, FileSource) -> Result<FileContent, AppError>,
+{
+    let mut warnings = Vec::new();
+
+    for comment in &mut review.comments {
+        let snippet = normalize(&comment.code_snippet);
+        if snippet.is_empty() {
+            continue;
+        }
+
+        // Old-side lines come from whatever the diff was taken against
+        let source = match (comment.is_old, comment.staged) {
+            (true, true) => FileSource::Head,
+            (true, false) | (false, true) => FileSource::Index,
+            (false, false) => FileSource::Workdir,
+        };
+        let lines = match read_file(&comment.file_path, source)? {
+            FileContent::Text(content) => numbered_lines(&content),
+            FileContent::Missing => Vec::new(),
+            FileContent::TooLarge => {
+                comment.freshness = None;
+                continue;
+            }
+        };
+
+        if matches_at(&lines, &snippet, comment.start_line, comment.end_line) {
+            comment.freshness = Some(Freshness::Current);
+            continue;
+        }
+
+        let original_ref = comment.line_ref();
+        let (freshness, message) = match find_range(&lines, &snippet, comment.start_line) {
+            Found::One(start, end) => {
+                let freshness = Freshness::Relocated {
+                    from_start_line: comment.start_line,
+                    from_end_line: comment.end_line,
+                };
+                comment.start_line = start;
+                comment.end_line = end;
+                let message = format!(
+                    "{}:{} moved to line {}",
+                    comment.file_path,
+                    original_ref,
+                    comment.line_ref()
+                );
+                (freshness, message)
+            }
+            Found::Several(candidate_lines) => {
+                let starts: Vec<String> = candidate_lines.iter().map(u32::to_string).collect();
+                let message = format!(
+                    "{}:{} moved, and matches lines {} equally well",
+                    comment.file_path,
+                    original_ref,
+                    starts.join(", ")
+                );
+                (Freshness::Ambiguous { candidate_lines }, message)
+            }
+            Found::None => {
+                let message = format!(
+                    "{}:{} no longer matches the commented code",
+                    comment.file_path, original_ref
+                );
+                (Freshness::Stale, message)
+            }
+        };
+
+        warnings.push(StaleWarning {
+            comment_id: comment.id.clone(),
+            file_path: comment.file_path.clone(),
+            start_line: comment.start_line,
+            end_line: comment.end_line,
+            freshness: freshness.clone(),
+            message,
+        });
+        comment.freshness = Some(freshness);
+    }
+
+    if !warnings.is_empty() {
+        review.sort_comments();
+    }
+    Ok(warnings)
+}
+
+fn matches_at(lines: &[(u32, String)], snippet: &[String], start: u32, end: u32) -> bool {
+    let in_place: Vec<&String> = lines
+        .iter()
+        .filter(|(n, _)| *n >= start && *n <= end)
+        .map(|(_, text)| text)
+        .collect();
+    in_place.len() == snippet.len() && in_place.iter().zip(snippet).all(|(a, b)| *a == b)
+}
+
+enum Found {
+    One(u32, u32),
+    /// Start lines of the matches tied for nearest.
+    Several(Vec<u32>),
+    None,
+}
+
+/// Finds the run of non-blank lines matching the snippet nearest to where the comment
+/// started, since short snippets like `}` can match all over a file.
+fn find_range(lines: &[(u32, String)], snippet: &[String], start_line: u32) -> Found {
+    let matches: Vec<(u32, u32)> = lines
+        .windows(snippet.len())
+        .filter(|window| window.iter().zip(snippet).all(|((_, a), b)| a == b))
+        .map(|window| (window[0].0, window[window.len() - 1].0))
+        .collect();
+    let Some(nearest) = matches
+        .iter()
+        .map(|(start, _)| start.abs_diff(start_line))
+        .min()
+    else {
+        return Found::None;
+    };
+
+    let closest: Vec<(u32, u32)> = matches
+        .into_iter()
+        .filter(|(start, _)| start.abs_diff(start_line) == nearest)
+        .collect();
+    match closest[..] {
+        [(start, end)] => Found::One(start, end),
+        _ => Found::Several(closest.iter().map(|(start, _)| *start).collect()),
+    }
+}
+
+/// Non-blank lines with their 1-based line numbers, trailing whitespace removed.
+fn numbered_lines(content: &str) -> Vec<(u32, String)> {
+    content
+        .lines()
+        .enumerate()
+        .map(|(i, line)| ((i + 1) as u32, line.trim_end().to_string()))
+        .filter(|(_, line)| !line.is_empty())
+        .collect()
+}
+
+/// Snippets are compared on their non-blank lines so line-ending and trailing-space
+/// differences don't count as changes.
+fn normalize(snippet: &str) -> Vec<String> {
+    snippet
+        .lines()
+        .map(|line| line.trim_end().to_string())
+        .filter(|line| !line.is_empty())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn commented(start_line: u32, end_line: u32, code_snippet: &str) -> ReviewComment {
+        ReviewComment {
+            id: "c1".to_string(),
+            file_path: "src/lib.rs".to_string(),
+            start_line,
+            end_line,
+            content: "Check this".to_string(),
+            category: CommentCategory::Issue,
+            code_snippet: code_snippet.to_string(),
+            created_at: 0,
+            is_old: false,
+            staged: false,
+            freshness: None,
+            hunk: None,
+            suggestion: None,
+            applied_suggestion: None,
+            replies: Vec::new(),
+            resolved: false,
+        }
+    }
+
+    fn check(comment: ReviewComment, content: FileContent) -> (ReviewComment, Vec<StaleWarning>) {
+        let mut review = Review::new(vec![comment]);
+        let warnings = check_freshness(&mut review, |path, source| {
+            assert_eq!((path, source), ("src/lib.rs", FileSource::Workdir));
+            Ok(content.clone())
+        })
+        .unwrap();
+        (review.comments.remove(0), warnings)
+    }
+
+    fn text(content: &str) -> FileContent {
+        FileContent::Text(content.to_string())
+    }
+
+    #[test]
+    fn unchanged_snippet_is_current() {
+        let content = text("fn a() {\n    one();  \n\n    two();\n}\n");
+        let (comment, warnings) =
+            check(commented(2, 4, "    one();\r\n\r\n    two();\r\n"), content);
+        assert_eq!(comment.freshness, Some(Freshness::Current));
+        assert!(warnings.is_empty());
+    }
+
+    #[test]
+    fn moved_snippet_relocates_to_nearest_match() {
+        let content = text("x\nmark\ny\ny\ny\ny\ny\nz\nmark\n");
+        let (comment, warnings) = check(commented(7, 7, "mark"), content);
+        assert_eq!((comment.start_line, comment.end_line), (9, 9));
+        assert_eq!(
+            comment.freshness,
+            Some(Freshness::Relocated {
+                from_start_line: 7,
+                from_end_line: 7,
+            })
+        );
+        assert_eq!(warnings.len(), 1);
+        assert_eq!(warnings[0].message, "src/lib.rs:7 moved to line 9");
+    }
+
+    #[test]
+    fn equally_near_matches_are_ambiguous() {
+        let content = text("}\nx\n}\n");
+        let (comment, warnings) = check(commented(2, 2, "}"), content);
+        assert_eq!((comment.start_line, comment.end_line), (2, 2));
+        assert_eq!(
+            comment.freshness,
+            Some(Freshness::Ambiguous {
+                candidate_lines: vec![1, 3],
+            })
+        );
+        assert_eq!(warnings.len(), 1);
+    }
+
+    #[test]
+    fn missing_snippet_is_stale() {
+        let (comment, warnings) = check(commented(1, 1, "gone();"), text("kept();\n"));
+        assert_eq!(comment.freshness, Some(Freshness::Stale));
+        assert_eq!(warnings[0].freshness, Freshness::Stale);
+
+        let (comment, _) = check(commented(1, 1, "gone();"), FileContent::Missing);
+        assert_eq!(comment.freshness, Some(Freshness::Stale));
+    }
+
+    #[test]
+    fn file_too_large_leaves_freshness_unknown() {
+        let mut stale = commented(1, 1, "gone();");
+        stale.freshness = Some(Freshness::Stale);
+        let (comment, warnings) = check(stale, FileContent::TooLarge);
+        assert_eq!(comment.freshness, None);
+        assert!(warnings.is_empty());
+    }
+}
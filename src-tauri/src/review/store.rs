@@ -0,0 +1,214 @@
+use serde::{Deserialize, Serialize};
+use std::fs::{self, File};
+use std::path::{Path, PathBuf};
+
+use super::patch_review::PatchReview;
+use super::types::{AppliedSuggestion, CommentCategory, CommentReply, ReviewComment};
+use crate::cache::RepoCache;
+use crate::compare::DirComparison;
+use crate::error::AppError;
+use crate::git::GitRepository;
+
+const STORE_VERSION: u32 = 1;
+
+#[derive(Serialize, Deserialize)]
+struct StoreFile {
+    version: u32,
+    comments: Vec<ReviewComment>,
+}
+
+/// Review comments for one repository, kept in its git directory (`.git/revu/comments.json`)
+/// so they travel with the repository when it is moved and are shared by every revu process.
+/// An open store holds a lock on the file until it is dropped, so changes made between
+/// opening and saving can't overwrite another thread's or process's.
+pub struct CommentStore {
+    path: PathBuf,
+    comments: Vec<ReviewComment>,
+    _lock: File,
+}
+
+impl CommentStore {
+    pub fn open(repo: &GitRepository) -> Result<Self, AppError> {
+        Self::open_at(repo.git_dir().join("revu").join("comments.json"))
+    }
+
+    /// Opens the store for a repository path or, for a patch review or directory comparison,
+    /// the store in its session directory.
+    pub fn for_path(repos: &RepoCache, repo_path: &str) -> Result<Self, AppError> {
+        let path = Path::new(repo_path);
+        if let Some(patch) = PatchReview::open(path)? {
+            return Self::open_at(patch.dir().join("comments.json"));
+        }
+        if let Some(dirs) = DirComparison::open(path)? {
+            return Self::open_at(dirs.dir().join("comments.json"));
+        }
+        repos.with_repo(repo_path, Self::open)
+    }
+
+    fn open_at(path: PathBuf) -> Result<Self, AppError> {
+        // Read only once the lock is held, so the store starts from the latest save
+        let lock = lock(&path)?;
+        let comments = match fs::read_to_string(&path) {
+            Ok(content) => {
+                let file: StoreFile = serde_json::from_str(&content).map_err(|e| {
+                    AppError::Custom(format!("Corrupt comment store {}: {}", path.display(), e))
+                })?;
+                file.comments
+            }
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Vec::new(),
+            Err(e) => return Err(e.into()),
+        };
+        Ok(Self {
+            path,
+            comments,
+            _lock: lock,
+        })
+    }
+
+    pub fn comments(&self) -> &[ReviewComment] {
+        &self.comments
+    }
+
+    pub fn add(&mut self, comment: ReviewComment) -> Result<(), AppError> {
+        if self.comments.iter().any(|c| c.id == comment.id) {
+            return Err(AppError::Custom(format!(
+                "Comment already exists: {}",
+                comment.id
+            )));
+        }
+        self.comments.push(stored(comment));
+        Ok(())
+    }
+
+    pub fn get(&self, id: &str) -> Result<&ReviewComment, AppError> {
+        self.comments
+            .iter()
+            .find(|c| c.id == id)
+            .ok_or_else(|| not_found(id))
+    }
+
+    pub fn update(
+        &mut self,
+        id: &str,
+        content: String,
+        category: CommentCategory,
+        suggestion: Option<String>,
+    ) -> Result<&ReviewComment, AppError> {
+        let comment = self.find_mut(id)?;
+        comment.content = content;
+        comment.category = category;
+        comment.suggestion = suggestion;
+        Ok(comment)
+    }
+
+    /// Records a suggestion as applied, which resolves the thread, or clears it after an
+    /// undo, which reopens it.
+    pub fn set_applied_suggestion(
+        &mut self,
+        id: &str,
+        applied: Option<AppliedSuggestion>,
+    ) -> Result<&ReviewComment, AppError> {
+        let comment = self.find_mut(id)?;
+        comment.resolved = applied.is_some();
+        comment.applied_suggestion = applied;
+        Ok(comment)
+    }
+
+    /// Appends a reply to a comment's thread. Replying reopens a resolved thread.
+    pub fn add_reply(&mut self, id: &str, reply: CommentReply) -> Result<&ReviewComment, AppError> {
+        let comment = self.find_mut(id)?;
+        comment.replies.push(reply);
+        comment.resolved = false;
+        Ok(comment)
+    }
+
+    pub fn remove_reply(&mut self, id: &str, reply_id: &str) -> Result<&ReviewComment, AppError> {
+        let comment = self.find_mut(id)?;
+        let before = comment.replies.len();
+        comment.replies.retain(|r| r.id != reply_id);
+        if comment.replies.len() == before {
+            return Err(AppError::Custom(format!("Reply not found: {}", reply_id)));
+        }
+        Ok(comment)
+    }
+
+    pub fn set_resolved(&mut self, id: &str, resolved: bool) -> Result<&ReviewComment, AppError> {
+        let comment = self.find_mut(id)?;
+        comment.resolved = resolved;
+        Ok(comment)
+    }
+
+    pub fn remove(&mut self, id: &str) -> Result<(), AppError> {
+        let before = self.comments.len();
+        self.comments.retain(|c| c.id != id);
+        if self.comments.len() == before {
+            return Err(not_found(id));
+        }
+        Ok(())
+    }
+
+    pub fn clear(&mut self) {
+        self.comments.clear();
+    }
+
+    /// Adds comments that aren't already stored, returning how many were added.
+    pub fn import(&mut self, comments: Vec<ReviewComment>) -> usize {
+        let before = self.comments.len();
+        for comment in comments {
+            if !self.comments.iter().any(|c| c.id == comment.id) {
+                self.comments.push(stored(comment));
+            }
+        }
+        self.comments.len() - before
+    }
+
+    /// Writes the store atomically so a concurrent reader never sees a partial file.
+    pub fn save(&self) -> Result<(), AppError> {
+        if let Some(dir) = self.path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let json = serde_json::to_string_pretty(&StoreFile {
+            version: STORE_VERSION,
+            comments: self.comments.clone(),
+        })
+        .map_err(|e| AppError::Custom(format!("Failed to serialise comments: {}", e)))?;
+
+        let tmp = self.path.with_extension("json.tmp");
+        fs::write(&tmp, json)?;
+        fs::rename(&tmp, &self.path)?;
+        Ok(())
+    }
+
+    fn find_mut(&mut self, id: &str) -> Result<&mut ReviewComment, AppError> {
+        self.comments
+            .iter_mut()
+            .find(|c| c.id == id)
+            .ok_or_else(|| not_found(id))
+    }
+}
+
+/// Takes an exclusive advisory lock on a file beside the store, which is released when the
+/// returned file is closed. The store itself can't be locked, as saving replaces it.
+fn lock(path: &Path) -> Result<File, AppError> {
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    let lock = fs::OpenOptions::new()
+        .create(true)
+        .truncate(false)
+        .write(true)
+        .open(path.with_extension("json.lock"))?;
+    lock.lock()?;
+    Ok(lock)
+}
+
+/// Export-time annotations are recomputed on every export and never stored.
+fn stored(mut comment: ReviewComment) -> ReviewComment {
+    comment.freshness = None;
+    comment.hunk = None;
+    comment
+}
+
+fn not_found(id: &str) -> AppError {
+    AppError::Custom(format!("Comment not found: {}", id))
+}
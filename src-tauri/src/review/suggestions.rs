@@ -0,0 +1,179 @@
+use git2::Patch;
+use std::fs;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use super::types::{AppliedSuggestion, ReviewComment};
+use crate::error::AppError;
+use crate::git::GitRepository;
+
+/// Replaces the comment's line range in the working-tree file with its suggestion, after
+/// checking those lines still hold the commented code. Returns the record needed to undo it.
+pub fn apply_suggestion(
+    repo: &GitRepository,
+    comment: &ReviewComment,
+) -> Result<AppliedSuggestion, AppError> {
+    let suggestion = comment
+        .suggestion
+        .as_deref()
+        .ok_or_else(|| AppError::Custom("Comment has no suggestion".to_string()))?;
+    if comment.is_old {
+        return Err(AppError::Custom(
+            "Suggestions can only be applied to new-side lines".to_string(),
+        ));
+    }
+    if comment.applied_suggestion.is_some() {
+        return Err(AppError::Custom(
+            "Suggestion has already been applied".to_string(),
+        ));
+    }
+
+    let workdir = repo
+        .workdir()
+        .ok_or_else(|| AppError::Custom("No working directory".to_string()))?;
+    let path = workdir.join(&comment.file_path);
+    let original = fs::read_to_string(&path)?;
+
+    if !lines_match(
+        &original,
+        comment.start_line,
+        comment.end_line,
+        &comment.code_snippet,
+    ) {
+        return Err(AppError::Custom(format!(
+            "{}:{} has changed since the suggestion was made",
+            comment.file_path,
+            comment.line_ref()
+        )));
+    }
+
+    let updated = replace_lines(&original, comment.start_line, comment.end_line, suggestion)?;
+
+    // Diffing new → old gives a patch that restores the original when applied
+    let mut reverse = Patch::from_buffers(
+        updated.as_bytes(),
+        Some(comment.file_path.as_ref()),
+        original.as_bytes(),
+        Some(comment.file_path.as_ref()),
+        None,
+    )?;
+    let reverse_patch = String::from_utf8_lossy(&reverse.to_buf()?).into_owned();
+
+    fs::write(&path, &updated)?;
+
+    Ok(AppliedSuggestion {
+        reverse_patch,
+        applied_at: SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_millis() as u64,
+    })
+}
+
+/// Reverts an applied suggestion. Fails, leaving the file alone, if the lines it touched
+/// have been edited since.
+pub fn undo_suggestion(repo: &GitRepository, applied: &AppliedSuggestion) -> Result<(), AppError> {
+    repo.apply_to_workdir(&applied.reverse_patch)
+        .map_err(|e| AppError::Custom(format!("Cannot undo suggestion: {}", e)))
+}
+
+/// Whether lines `start..=end` (1-based) are exactly the commented code, ignoring only line
+/// endings. The diff view stores a snippet as its lines, each with its newline, joined by
+/// newlines.
+fn lines_match(content: &str, start: u32, end: u32, snippet: &str) -> bool {
+    let lines: Vec<&str> = content.lines().collect();
+    let (start, end) = (start as usize, end as usize);
+    if start == 0 || end < start || end > lines.len() {
+        return false;
+    }
+    let snippet = snippet.replace("\r\n", "\n");
+    let snippet = snippet.strip_suffix('\n').unwrap_or(&snippet);
+    snippet == lines[start - 1..end].join("\n\n")
+}
+
+/// Swaps lines `start..=end` (1-based) for the replacement, keeping the file's line endings.
+fn replace_lines(
+    content: &str,
+    start: u32,
+    end: u32,
+    replacement: &str,
+) -> Result<String, AppError> {
+    let lines: Vec<&str> = content.split_inclusive('\n').collect();
+    let (start, end) = (start as usize, end as usize);
+    if start == 0 || end < start || end > lines.len() {
+        return Err(AppError::Custom(format!(
+            "Lines {}-{} are outside the file",
+            start, end
+        )));
+    }
+
+    let newline = if lines[0].ends_with("\r\n") {
+        "\r\n"
+    } else {
+        "\n"
+    };
+    // The last replaced line decides whether the replacement ends with a newline
+    let trailing = lines[end - 1].ends_with('\n');
+
+    let mut out: String = lines[..start - 1].concat();
+    let replacement_lines: Vec<&str> = replacement.lines().collect();
+    for (i, line) in replacement_lines.iter().enumerate() {
+        out.push_str(line);
+        if i + 1 < replacement_lines.len() || trailing {
+            out.push_str(newline);
+        }
+    }
+    // Deleting the last lines of a file without a final newline keeps it without one
+    if end == lines.len() && !trailing && replacement_lines.is_empty() {
+        let kept = out
+            .strip_suffix('\n')
+            .map(|s| s.strip_suffix('\r').unwrap_or(s));
+        out.truncate(kept.map_or(out.len(), str::len));
+    }
+    out.push_str(&lines[end..].concat());
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn replaces_lines_keeping_crlf() {
+        let updated = replace_lines("a\r\nb\r\nc\r\n", 2, 2, "x\ny").unwrap();
+        assert_eq!(updated, "a\r\nx\r\ny\r\nc\r\n");
+    }
+
+    #[test]
+    fn keeps_missing_final_newline() {
+        assert_eq!(replace_lines("a\nb", 2, 2, "x\ny").unwrap(), "a\nx\ny");
+        assert_eq!(replace_lines("a\nb", 1, 1, "x").unwrap(), "x\nb");
+    }
+
+    #[test]
+    fn deletes_last_lines() {
+        assert_eq!(replace_lines("a\nb\nc\n", 2, 3, "").unwrap(), "a\n");
+        assert_eq!(replace_lines("a\r\nb\r\nc", 2, 3, "").unwrap(), "a");
+        assert_eq!(replace_lines("a\n\nb", 3, 3, "").unwrap(), "a\n");
+    }
+
+    #[test]
+    fn empty_suggestion_deletes_lines() {
+        assert_eq!(replace_lines("a\nb\nc\n", 2, 2, "").unwrap(), "a\nc\n");
+        assert_eq!(replace_lines("a\n", 1, 1, "").unwrap(), "");
+    }
+
+    #[test]
+    fn rejects_lines_outside_the_file() {
+        assert!(replace_lines("a\nb\n", 2, 3, "x").is_err());
+        assert!(replace_lines("a\n", 0, 1, "x").is_err());
+    }
+
+    #[test]
+    fn matches_snippet_exactly() {
+        let content = "fn a() {\r\n    1  \r\n\r\n}\r\n";
+        assert!(lines_match(content, 2, 4, "    1  \r\n\n\r\n\n}\r\n"));
+        assert!(!lines_match(content, 2, 4, "    1\n\n\n\n}\n"));
+        assert!(!lines_match(content, 2, 3, "    1  \n"));
+        assert!(lines_match("a\nb", 2, 2, "b"));
+    }
+}
@@ -0,0 +1,347 @@
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+use crate::git::{DiffHunk, FileDiff};
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum CommentCategory {
+    Issue,
+    Suggestion,
+    Question,
+    Nitpick,
+    Praise,
+}
+
+impl CommentCategory {
+    pub const ALL: [CommentCategory; 5] = [
+        CommentCategory::Issue,
+        CommentCategory::Suggestion,
+        CommentCategory::Question,
+        CommentCategory::Nitpick,
+        CommentCategory::Praise,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            CommentCategory::Issue => "issue",
+            CommentCategory::Suggestion => "suggestion",
+            CommentCategory::Question => "question",
+            CommentCategory::Nitpick => "nitpick",
+            CommentCategory::Praise => "praise",
+        }
+    }
+
+    pub fn description(&self) -> &'static str {
+        match self {
+            CommentCategory::Issue => "Bug or error — must be fixed",
+            CommentCategory::Suggestion => "Improvement — implement unless problematic",
+            CommentCategory::Question => "Clarification needed — explain your reasoning",
+            CommentCategory::Nitpick => "Minor preference — fix if easy",
+            CommentCategory::Praise => "Positive feedback — no change needed",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReviewComment {
+    pub id: String,
+    pub file_path: String,
+    pub start_line: u32,
+    pub end_line: u32,
+    pub content: String,
+    pub category: CommentCategory,
+    #[serde(default)]
+    pub code_snippet: String,
+    #[serde(default)]
+    pub created_at: u64,
+    /// true = old/deletion side, false = new/addition side
+    pub is_old: bool,
+    /// Whether the comment was made on the staged (HEAD → index) view of the file.
+    #[serde(default)]
+    pub staged: bool,
+    /// Result of re-checking the code snippet against the file at export time.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub freshness: Option<Freshness>,
+    /// The diff hunk enclosing the comment, attached at export time when requested.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub hunk: Option<DiffHunk>,
+    /// Replacement text for the commented lines (new side only), applied as-is.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub suggestion: Option<String>,
+    /// Set once the suggestion has been written to the working tree.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub applied_suggestion: Option<AppliedSuggestion>,
+    /// Follow-up discussion from the reviewer and the agent, oldest first.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub replies: Vec<CommentReply>,
+    /// Resolved threads are kept for reference but left out of exports.
+    #[serde(default)]
+    pub resolved: bool,
+}
+
+/// A suggestion written to the working tree, with the patch that reverts it.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct AppliedSuggestion {
+    pub reverse_patch: String,
+    /// Milliseconds since the Unix epoch.
+    pub applied_at: u64,
+}
+
+/// How the agent says it dealt with a comment.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum ReplyStatus {
+    /// The requested change was made.
+    Addressed,
+    /// The agent disagrees and explains why.
+    Disputed,
+    /// A question was answered.
+    Answered,
+}
+
+impl ReplyStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ReplyStatus::Addressed => "addressed",
+            ReplyStatus::Disputed => "disputed",
+            ReplyStatus::Answered => "answered",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum ReplyAuthor {
+    Reviewer,
+    Agent,
+}
+
+impl ReplyAuthor {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ReplyAuthor::Reviewer => "reviewer",
+            ReplyAuthor::Agent => "agent",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct CommentReply {
+    pub id: String,
+    pub author: ReplyAuthor,
+    pub content: String,
+    /// Milliseconds since the Unix epoch.
+    pub created_at: u64,
+    /// How the agent says it dealt with the comment; only set on agent replies.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub status: Option<ReplyStatus>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(
+    tag = "state",
+    rename_all = "lowercase",
+    rename_all_fields = "camelCase"
+)]
+pub enum Freshness {
+    /// The snippet still matches at the commented lines.
+    Current,
+    /// The snippet was found elsewhere in the file and the comment moved with it.
+    Relocated {
+        from_start_line: u32,
+        from_end_line: u32,
+    },
+    /// The snippet moved and now appears in several places equally close to where it was,
+    /// so the comment was left at its original lines.
+    Ambiguous { candidate_lines: Vec<u32> },
+    /// The snippet no longer exists in the file.
+    Stale,
+}
+
+/// A comment whose snippet no longer matches where it was made.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StaleWarning {
+    pub comment_id: String,
+    pub file_path: String,
+    pub start_line: u32,
+    pub end_line: u32,
+    pub freshness: Freshness,
+    pub message: String,
+}
+
+impl ReviewComment {
+    pub fn side(&self) -> &'static str {
+        if self.is_old {
+            "old"
+        } else {
+            "new"
+        }
+    }
+
+    /// Explains a relocated or stale comment to the reader; `None` when the comment is current.
+    pub fn freshness_note(&self) -> Option<String> {
+        match self.freshness.as_ref()? {
+            Freshness::Current => None,
+            Freshness::Relocated {
+                from_start_line,
+                from_end_line,
+            } => {
+                let from = if from_start_line == from_end_line {
+                    from_start_line.to_string()
+                } else {
+                    format!("{}-{}", from_start_line, from_end_line)
+                };
+                Some(format!(
+                    "relocated: the code moved from line {} since the comment was made",
+                    from
+                ))
+            }
+            Freshness::Ambiguous { candidate_lines } => {
+                let lines: Vec<String> = candidate_lines.iter().map(u32::to_string).collect();
+                Some(format!(
+                    "ambiguous: the commented code moved and now appears at lines {}",
+                    lines.join(", ")
+                ))
+            }
+            Freshness::Stale => {
+                Some("stale: the commented code no longer exists in the file".to_string())
+            }
+        }
+    }
+
+    /// The status from the agent's most recent reply, if it has replied.
+    pub fn agent_status(&self) -> Option<ReplyStatus> {
+        self.replies
+            .iter()
+            .rev()
+            .find(|r| r.author == ReplyAuthor::Agent)
+            .and_then(|r| r.status)
+    }
+
+    pub fn line_ref(&self) -> String {
+        if self.start_line == self.end_line {
+            self.start_line.to_string()
+        } else {
+            format!("{}-{}", self.start_line, self.end_line)
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct ExportOptions {
+    /// Embed the enclosing diff hunk alongside each comment.
+    pub include_hunks: bool,
+    /// Context lines around the changed lines of each embedded hunk.
+    pub hunk_context_lines: u32,
+}
+
+impl Default for ExportOptions {
+    fn default() -> Self {
+        Self {
+            include_hunks: false,
+            hunk_context_lines: 3,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Review {
+    /// Identifies the export so the agent's reply file can be matched back to it.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
+    /// Where the agent should write its reply, as the agent sees the path.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reply_path: Option<String>,
+    /// Repository root as the agent sees it, after path mapping.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub repository: Option<String>,
+    pub comments: Vec<ReviewComment>,
+    /// Diffs of the reviewed changes, loaded only for formats that render them.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub files: Vec<FileDiff>,
+}
+
+impl Review {
+    /// Builds a review with comments ordered by file, then by starting line.
+    pub fn new(comments: Vec<ReviewComment>) -> Self {
+        let mut review = Self {
+            id: None,
+            reply_path: None,
+            repository: None,
+            comments,
+            files: Vec::new(),
+        };
+        review.sort_comments();
+        review
+    }
+
+    pub fn sort_comments(&mut self) {
+        self.comments.sort_by(|a, b| {
+            a.file_path
+                .cmp(&b.file_path)
+                .then(a.start_line.cmp(&b.start_line))
+        });
+    }
+
+    pub fn with_files(mut self, files: Vec<FileDiff>) -> Self {
+        self.files = files;
+        self
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.comments.is_empty()
+    }
+
+    /// Rewrites comment file paths from repository-relative to absolute under `repository`.
+    pub fn use_absolute_paths(&mut self) {
+        let Some(root) = &self.repository else {
+            return;
+        };
+        for comment in &mut self.comments {
+            comment.file_path = Path::new(root)
+                .join(&comment.file_path)
+                .to_string_lossy()
+                .to_string();
+        }
+    }
+
+    pub fn count(&self, category: CommentCategory) -> usize {
+        self.comments
+            .iter()
+            .filter(|c| c.category == category)
+            .count()
+    }
+
+    pub fn summary(&self) -> ReviewSummary {
+        let issues = self.count(CommentCategory::Issue);
+        let suggestions = self.count(CommentCategory::Suggestion);
+        ReviewSummary {
+            total: self.comments.len(),
+            action_required: issues + suggestions,
+            issues,
+            suggestions,
+            questions: self.count(CommentCategory::Question),
+            nitpicks: self.count(CommentCategory::Nitpick),
+            praise: self.count(CommentCategory::Praise),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReviewSummary {
+    pub total: usize,
+    pub action_required: usize,
+    pub issues: usize,
+    pub suggestions: usize,
+    pub questions: usize,
+    pub nitpicks: usize,
+    pub praise: usize,
+}
@@ -0,0 +1,264 @@
+use ignore::WalkBuilder;
+use notify::event::ModifyKind;
+use notify::{Event, EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use serde::Serialize;
+use std::collections::BTreeSet;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use crate::config::WatchConfig;
+use crate::error::AppError;
+use crate::git::GitRepository;
+
+/// A batch of changes to the watched repository.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RepoChanged {
+    /// The repository path the watch was started with.
+    pub repository: String,
+    /// Changed working-tree paths, relative to the repository root.
+    pub paths: Vec<String>,
+    /// Whether the index or HEAD changed, e.g. by a stage, commit or checkout.
+    pub git: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RepoSettled {
+    pub repository: String,
+}
+
+#[derive(Debug, Clone)]
+pub enum WatchEvent {
+    Changed(RepoChanged),
+    Settled(RepoSettled),
+}
+
+/// Watches a repository's working tree until dropped.
+pub struct RepoWatcher {
+    repository: String,
+    /// The event thread only holds a weak reference, so dropping this stops it.
+    _watcher: Arc<Mutex<RecommendedWatcher>>,
+}
+
+impl RepoWatcher {
+    pub fn repository(&self) -> &str {
+        &self.repository
+    }
+}
+
+/// Starts watching the repository. Changes are batched until `debounce_ms` passes without
+/// another, then reported; once `settle_ms` passes with no further changes the repository
+/// is reported as settled. Ignored files and `.git` internals other than `index` and
+/// `HEAD` are left out, and ignored directories aren't watched at all.
+pub fn watch_repo<F>(
+    repository: &str,
+    repo: GitRepository,
+    config: &WatchConfig,
+    on_event: F,
+) -> Result<RepoWatcher, AppError>
+where
+    F: Fn(WatchEvent) + Send + 'static,
+{
+    let workdir = repo
+        .workdir()
+        .ok_or_else(|| AppError::Custom("No working directory".to_string()))?
+        .to_path_buf();
+    let git_dir = repo.git_dir().to_path_buf();
+
+    let (tx, rx) = mpsc::channel::<Event>();
+    let mut watcher = notify::recommended_watcher(move |event: notify::Result<Event>| {
+        // Reads (including our own ignore checks) show up as access events; skip them
+        match event {
+            Ok(event) if !matches!(event.kind, EventKind::Access(_)) => {
+                let _ = tx.send(event);
+            }
+            _ => {}
+        }
+    })
+    .map_err(|e| AppError::Custom(format!("Failed to watch repository: {}", e)))?;
+    let watched = if PER_DIRECTORY {
+        watch_tree(&mut watcher, &workdir, |_| {})
+    } else {
+        watcher.watch(&workdir, RecursiveMode::Recursive)
+    };
+    watched
+        .and_then(|_| {
+            // Unwatched by the walk, or outside the working tree for a linked worktree
+            if PER_DIRECTORY || !git_dir.starts_with(&workdir) {
+                watcher.watch(&git_dir, RecursiveMode::NonRecursive)?;
+            }
+            Ok(())
+        })
+        .map_err(|e| AppError::Custom(format!("Failed to watch repository: {}", e)))?;
+    let watcher = Arc::new(Mutex::new(watcher));
+    let new_dirs = Arc::downgrade(&watcher);
+
+    let debounce = Duration::from_millis(config.debounce_ms);
+    let settle = Duration::from_millis(config.settle_ms);
+    let filter = ChangeFilter {
+        repo,
+        workdir,
+        git_dir,
+    };
+    let name = repository.to_string();
+
+    // The channel closes when the watcher is dropped, which ends this thread
+    std::thread::spawn(move || {
+        let mut paths = BTreeSet::new();
+        let mut git = false;
+        let mut last_change: Option<Instant> = None;
+        let mut settle_at: Option<Instant> = None;
+
+        loop {
+            let deadline = match last_change {
+                Some(at) => Some(at + debounce),
+                None => settle_at,
+            };
+            let event = match deadline {
+                Some(deadline) => {
+                    rx.recv_timeout(deadline.saturating_duration_since(Instant::now()))
+                }
+                None => rx.recv().map_err(|_| RecvTimeoutError::Disconnected),
+            };
+
+            match event {
+                Ok(event) => {
+                    let mut changed = false;
+                    let added = matches!(
+                        event.kind,
+                        EventKind::Create(_) | EventKind::Modify(ModifyKind::Name(_))
+                    );
+                    for path in &event.paths {
+                        match filter.classify(path) {
+                            Change::Path(relative) => {
+                                changed = true;
+                                paths.insert(relative);
+                                // Watch new directories too, and pick up files written
+                                // into them before the watch was in place
+                                if PER_DIRECTORY && added && path.is_dir() {
+                                    if let Some(watcher) = new_dirs.upgrade() {
+                                        // Nothing is left half-done if a watch call
+                                        // panicked, so carry on with a poisoned lock
+                                        let mut watcher =
+                                            watcher.lock().unwrap_or_else(|e| e.into_inner());
+                                        let _ = watch_tree(&mut watcher, path, |file| {
+                                            if let Change::Path(file) = filter.classify(file) {
+                                                paths.insert(file);
+                                            }
+                                        });
+                                    }
+                                }
+                            }
+                            Change::Git => {
+                                changed = true;
+                                git = true;
+                            }
+                            Change::Ignored => {}
+                        }
+                    }
+                    // Both periods run from the latest change, even one to a path that's
+                    // already in the batch
+                    if changed {
+                        let now = Instant::now();
+                        last_change = Some(now);
+                        settle_at = Some(now + settle);
+                    }
+                }
+                Err(RecvTimeoutError::Timeout) if last_change.is_some() => {
+                    last_change = None;
+                    on_event(WatchEvent::Changed(RepoChanged {
+                        repository: name.clone(),
+                        paths: std::mem::take(&mut paths).into_iter().collect(),
+                        git: std::mem::take(&mut git),
+                    }));
+                }
+                Err(RecvTimeoutError::Timeout) => {
+                    settle_at = None;
+                    on_event(WatchEvent::Settled(RepoSettled {
+                        repository: name.clone(),
+                    }));
+                }
+                Err(RecvTimeoutError::Disconnected) => break,
+            }
+        }
+    });
+
+    Ok(RepoWatcher {
+        repository: repository.to_string(),
+        _watcher: watcher,
+    })
+}
+
+/// Whether directories are watched one at a time. A recursive watch with inotify or kqueue
+/// adds a watch for every directory, including `node_modules`, `target` and the like, and
+/// on Linux each takes one of the user's limited inotify watches. FSEvents and
+/// `ReadDirectoryChangesW` watch a whole tree with one handle, so keep doing that there.
+const PER_DIRECTORY: bool = !cfg!(any(target_os = "macos", target_os = "windows"));
+
+/// Watches `dir` and each directory under it that git doesn't ignore. `on_file` is called
+/// with every file found.
+fn watch_tree(
+    watcher: &mut RecommendedWatcher,
+    dir: &Path,
+    mut on_file: impl FnMut(&Path),
+) -> notify::Result<()> {
+    let walker = WalkBuilder::new(dir)
+        .hidden(false)
+        .filter_entry(|entry| entry.file_name() != ".git")
+        .build();
+    for entry in walker.filter_map(Result::ok) {
+        if entry.file_type().is_some_and(|t| t.is_dir()) {
+            match watcher.watch(entry.path(), RecursiveMode::NonRecursive) {
+                Ok(()) => {}
+                // A subdirectory may be gone already, but running out of watches isn't
+                Err(e)
+                    if entry.depth() > 0 && !matches!(e.kind, notify::ErrorKind::MaxFilesWatch) => {
+                }
+                Err(e) => return Err(e),
+            }
+        } else {
+            on_file(entry.path());
+        }
+    }
+    Ok(())
+}
+
+enum Change {
+    Path(String),
+    Git,
+    Ignored,
+}
+
+struct ChangeFilter {
+    repo: GitRepository,
+    workdir: PathBuf,
+    git_dir: PathBuf,
+}
+
+impl ChangeFilter {
+    fn classify(&self, path: &Path) -> Change {
+        if let Ok(inside_git) = path.strip_prefix(&self.git_dir) {
+            return if inside_git == Path::new("index") || inside_git == Path::new("HEAD") {
+                Change::Git
+            } else {
+                Change::Ignored
+            };
+        }
+
+        let Ok(relative) = path.strip_prefix(&self.workdir) else {
+            return Change::Ignored;
+        };
+        let parts: Vec<_> = relative
+            .components()
+            .map(|c| c.as_os_str().to_string_lossy())
+            .collect();
+        let relative = parts.join("/");
+        if relative.is_empty() || parts[0] == ".git" || self.repo.is_ignored(&relative) {
+            return Change::Ignored;
+        }
+        Change::Path(relative)
+    }
+}
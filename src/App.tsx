@@ -1,6 +1,8 @@
-import { useEffect, useRef } from "react";
+import { useCallback, useEffect, useRef } from "react";
 import { open } from "@tauri-apps/plugin-dialog";
+import { invoke } from "@tauri-apps/api/core";
 import { listen } from "@tauri-apps/api/event";
+import { readText } from "@tauri-apps/plugin-clipboard-manager";
 import { useGitStore } from "@/stores/gitStore";
 import { useCommentStore } from "@/stores/commentStore";
 import { useUiStore } from "@/stores/uiStore";
@@ -9,15 +11,42 @@ import { FileList } from "@/features/files";
 import { DiffViewer } from "@/features/diff";
 import { CommentPopover, CommentList } from "@/features/comments";
 import { CommitPanel } from "@/features/commit";
+import { ReviewHistory } from "@/features/history";
+import { PatchPreview } from "@/features/patch";
 import { Button } from "@/components/ui";
+import type {
+  DirComparison,
+  OpenedPatchReview,
+  RepoChanged,
+  RepoSettled,
+} from "@/types/git";
+import type { ReplyBatch } from "@/types/review";
 
 export default function App() {
-  const { repoPath, status, setRepoPath, refreshStatus, initDemoMode, isDemo } =
-    useGitStore();
+  const {
+    repoPath,
+    status,
+    setRepoPath,
+    refreshStatus,
+    initDemoMode,
+    isDemo,
+    patchPreview,
+    patchName,
+    importPatch,
+    openPatchReview,
+    initPatchReview,
+    comparison,
+    openComparison,
+    initComparison,
+    isChanging,
+    handleRepoChanged,
+    handleRepoSettled,
+  } = useGitStore();
   const {
     draft,
-    exportToMarkdown,
+    renderReview,
     setRepoPath: setCommentRepoPath,
+    loadComments,
     getAllComments,
     initDemoComments,
   } = useCommentStore();
@@ -25,8 +54,14 @@ export default function App() {
     showCommentsPanel,
     toggleCommentsPanel,
     setShowCommentsPanel,
+    showHistoryPanel,
+    toggleHistoryPanel,
     setTheme,
     sidebarWidth,
+    exportFormat,
+    exportOptions,
+    showFullFileContext,
+    ignoreWhitespace,
   } = useUiStore();
 
   // Initialize demo mode on mount (only in development)
@@ -84,14 +119,111 @@ export default function App() {
     };
   }, [setRepoPath]);
 
+  // Preview a patch passed with --patch
+  useEffect(() => {
+    const unlisten = listen<string>("import-patch", (event) => {
+      importPatch({ patch: event.payload });
+    });
+
+    return () => {
+      unlisten.then((fn) => fn());
+    };
+  }, [importPatch]);
+
+  // Review a patch given without a repository (`revu -`, `revu review <file>`)
+  useEffect(() => {
+    const unlisten = listen<OpenedPatchReview>("review-patch", (event) => {
+      initPatchReview(event.payload);
+    });
+
+    return () => {
+      unlisten.then((fn) => fn());
+    };
+  }, [initPatchReview]);
+
+  // Compare two directories given with `revu compare <left> <right>`
+  useEffect(() => {
+    const unlisten = listen<DirComparison>("compare-dirs", (event) => {
+      initComparison(event.payload);
+    });
+
+    return () => {
+      unlisten.then((fn) => fn());
+    };
+  }, [initComparison]);
+
+  // Watch the open repository so edits made by an agent show up without a manual refresh
+  useEffect(() => {
+    if (!repoPath || isDemo) return;
+    invoke("watch_repository", { repoPath }).catch((err) =>
+      console.error("Failed to watch repository:", err),
+    );
+  }, [repoPath, isDemo]);
+
+  useEffect(() => {
+    const unlistenChanged = listen<RepoChanged>("repo-changed", (event) => {
+      handleRepoChanged(event.payload, showFullFileContext, ignoreWhitespace);
+    });
+    const unlistenSettled = listen<RepoSettled>("repo-settled", (event) => {
+      handleRepoSettled(event.payload);
+    });
+
+    return () => {
+      unlistenChanged.then((fn) => fn());
+      unlistenSettled.then((fn) => fn());
+    };
+  }, [
+    handleRepoChanged,
+    handleRepoSettled,
+    showFullFileContext,
+    ignoreWhitespace,
+  ]);
+
+  // Pick up agent replies to exported reviews
+  useEffect(() => {
+    const unlisten = listen<ReplyBatch>("agent-replies", async (event) => {
+      const { repository, replies } = event.payload;
+      // Replies to a review revu has no record of apply to the open repository
+      if (!repository && repoPath && !isDemo) {
+        try {
+          await invoke<number>("apply_agent_replies", { repoPath, replies });
+        } catch (err) {
+          console.error("Failed to apply agent replies:", err);
+        }
+      }
+      loadComments();
+    });
+
+    return () => {
+      unlisten.then((fn) => fn());
+    };
+  }, [repoPath, isDemo, loadComments]);
+
+  // With a repository open a patch is previewed against it, otherwise it's reviewed on its own
+  const loadPatch = useCallback(
+    (source: { patch?: string; path?: string }) =>
+      repoPath && !patchName && !comparison
+        ? importPatch(source)
+        : openPatchReview(source),
+    [repoPath, patchName, comparison, importPatch, openPatchReview],
+  );
+
   useEffect(() => {
     const handleKeyDown = (e: KeyboardEvent) => {
       if ((e.metaKey || e.ctrlKey) && e.key === "c" && e.shiftKey) {
         e.preventDefault();
-        const markdown = exportToMarkdown();
-        if (markdown) {
-          navigator.clipboard.writeText(markdown);
-        }
+        renderReview(exportFormat, exportOptions).then((review) => {
+          if (review) {
+            navigator.clipboard.writeText(review);
+          }
+        });
+      }
+
+      if ((e.metaKey || e.ctrlKey) && e.key === "v" && e.shiftKey) {
+        e.preventDefault();
+        readText().then((text) => {
+          if (text) loadPatch({ patch: text });
+        });
       }
 
       if ((e.metaKey || e.ctrlKey) && e.key === "r" && !e.shiftKey) {
@@ -102,7 +234,7 @@ export default function App() {
 
     window.addEventListener("keydown", handleKeyDown);
     return () => window.removeEventListener("keydown", handleKeyDown);
-  }, [exportToMarkdown, refreshStatus]);
+  }, [renderReview, exportFormat, exportOptions, refreshStatus, loadPatch]);
 
   // Auto-open comments panel when first comment is added
   const comments = getAllComments();
@@ -132,6 +264,34 @@ export default function App() {
     }
   };
 
+  const handleImportPatch = async () => {
+    const selected = await open({
+      multiple: false,
+      title: "Import Patch",
+      filters: [{ name: "Patch", extensions: ["patch", "diff"] }],
+    });
+    if (selected) {
+      loadPatch({ path: selected });
+    }
+  };
+
+  const handleCompare = async () => {
+    const left = await open({
+      directory: true,
+      multiple: false,
+      title: "Select Original Directory",
+    });
+    if (!left) return;
+    const right = await open({
+      directory: true,
+      multiple: false,
+      title: "Select Changed Directory",
+    });
+    if (right) {
+      openComparison(left, right);
+    }
+  };
+
   return (
     <ThemeProvider>
       <div className="h-full flex flex-col bg-white dark:bg-gray-900">
@@ -148,7 +308,9 @@ export default function App() {
               {repoPath ? (
                 <>
                   <span className="text-gray-900 dark:text-gray-100 font-medium">
-                    {repoPath.split("/").pop()}
+                    {comparison
+                      ? `${comparison.left.split("/").pop()} → ${comparison.name}`
+                      : (patchName ?? repoPath.split("/").pop())}
                   </span>
                   {status?.branch && (
                     <>
@@ -178,6 +340,12 @@ export default function App() {
                 </span>
               )}
             </button>
+            {isChanging && (
+              <span
+                className="w-2 h-2 rounded-full bg-amber-400 animate-pulse"
+                title="Files are changing"
+              />
+            )}
             <Button
               variant="ghost"
               size="sm"
@@ -198,6 +366,48 @@ export default function App() {
                 />
               </svg>
             </Button>
+            <Button
+              variant={patchPreview ? "secondary" : "ghost"}
+              size="sm"
+              onClick={handleImportPatch}
+              disabled={isDemo}
+              title="Import patch (Cmd+Shift+V from clipboard)"
+            >
+              <svg
+                className="w-4 h-4"
+                fill="none"
+                viewBox="0 0 24 24"
+                stroke="currentColor"
+              >
+                <path
+                  strokeLinecap="round"
+                  strokeLinejoin="round"
+                  strokeWidth={2}
+                  d="M4 16v1a3 3 0 003 3h10a3 3 0 003-3v-1m-4-8l-4-4m0 0L8 8m4-4v12"
+                />
+              </svg>
+            </Button>
+            <Button
+              variant={comparison ? "secondary" : "ghost"}
+              size="sm"
+              onClick={handleCompare}
+              disabled={isDemo}
+              title="Compare two directories"
+            >
+              <svg
+                className="w-4 h-4"
+                fill="none"
+                viewBox="0 0 24 24"
+                stroke="currentColor"
+              >
+                <path
+                  strokeLinecap="round"
+                  strokeLinejoin="round"
+                  strokeWidth={2}
+                  d="M8 7h12m0 0l-4-4m4 4l-4 4m0 6H4m0 0l4 4m-4-4l4-4"
+                />
+              </svg>
+            </Button>
             <Button
               variant={showCommentsPanel ? "secondary" : "ghost"}
               size="sm"
@@ -218,6 +428,26 @@ export default function App() {
                 />
               </svg>
             </Button>
+            <Button
+              variant={showHistoryPanel ? "secondary" : "ghost"}
+              size="sm"
+              onClick={toggleHistoryPanel}
+              title="Toggle review history"
+            >
+              <svg
+                className="w-4 h-4"
+                fill="none"
+                viewBox="0 0 24 24"
+                stroke="currentColor"
+              >
+                <path
+                  strokeLinecap="round"
+                  strokeLinejoin="round"
+                  strokeWidth={2}
+                  d="M12 8v4l3 3m6-3a9 9 0 11-18 0 9 9 0 0118 0z"
+                />
+              </svg>
+            </Button>
             <ThemeToggle />
           </div>
         </header>
@@ -230,9 +460,9 @@ export default function App() {
             style={{ width: sidebarWidth }}
           >
             <div className="flex-1 min-h-0 overflow-y-auto">
-              <FileList />
+              {patchPreview ? <PatchPreview /> : <FileList />}
             </div>
-            <CommitPanel />
+            {!patchName && !comparison && <CommitPanel />}
           </aside>
 
           {/* Diff viewer */}
@@ -240,10 +470,10 @@ export default function App() {
             <DiffViewer />
           </main>
 
-          {/* Comments panel */}
-          {showCommentsPanel && (
+          {/* Comments panel (or past reviews) */}
+          {(showCommentsPanel || showHistoryPanel) && (
             <aside className="w-72 flex-shrink-0 border-l border-gray-200 dark:border-gray-700 bg-white dark:bg-gray-900">
-              <CommentList />
+              {showHistoryPanel ? <ReviewHistory /> : <CommentList />}
             </aside>
           )}
         </div>
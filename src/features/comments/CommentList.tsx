@@ -2,14 +2,22 @@ import { useRef, useState, useEffect } from "react";
 import { clsx } from "clsx";
 import { invoke } from "@tauri-apps/api/core";
 import { writeText } from "@tauri-apps/plugin-clipboard-manager";
+import { ask, message } from "@tauri-apps/plugin-dialog";
 import { useCommentStore } from "@/stores/commentStore";
 import { useGitStore } from "@/stores/gitStore";
 import { useUiStore } from "@/stores/uiStore";
-import { Button } from "@/components/ui";
+import { Button, Checkbox } from "@/components/ui";
 import { HighlightedContent } from "@/features/diff/HighlightedContent";
 import { getLanguageFromPath } from "@/lib/syntax";
 import { stripIndent } from "@/lib/stripIndent";
-import type { Comment, CommentCategory } from "@/types/comment";
+import type { Comment, CommentCategory, ReplyStatus } from "@/types/comment";
+import {
+  exportFormatLabels,
+  type DeliveryReport,
+  type ExportFormat,
+  type ExportResult,
+  type StaleWarning,
+} from "@/types/review";
 
 const categoryStyles: Record<
   CommentCategory,
@@ -37,24 +45,43 @@ const categoryStyles: Record<
   },
 };
 
+const replyStatusStyles: Record<ReplyStatus, string> = {
+  addressed: "bg-green-100 text-green-700 dark:bg-green-900/50 dark:text-green-300",
+  disputed: "bg-orange-100 text-orange-700 dark:bg-orange-900/50 dark:text-orange-300",
+  answered: "bg-blue-100 text-blue-700 dark:bg-blue-900/50 dark:text-blue-300",
+};
+
 export function CommentList() {
   const {
     getAllComments,
     removeComment,
-    exportToMarkdown,
+    renderReview,
     clearAllComments,
     setDraft,
   } = useCommentStore();
-  const { repoPath, status, selectFile } = useGitStore();
-  const { setScrollToLine } = useUiStore();
+  const { repoPath, status, patchPreview, selectFile } = useGitStore();
+  const {
+    setScrollToLine,
+    exportFormat,
+    setExportFormat,
+    exportOptions,
+    setExportOptions,
+  } = useUiStore();
   const comments = getAllComments();
+  const resolvedCount = comments.filter((c) => c.resolved).length;
   const [exportStatus, setExportStatus] = useState<
     "idle" | "exporting" | "exported"
   >("idle");
+  const [failedDeliveries, setFailedDeliveries] = useState<DeliveryReport[]>(
+    [],
+  );
 
   const handleNavigate = (comment: Comment) => {
-    // Find the file in status and select it
-    const file = status?.files.find((f) => f.path === comment.filePath);
+    // Find the file in status (or the previewed patch) and select it
+    const diff = patchPreview?.files.find((f) => f.path === comment.filePath);
+    const file = diff
+      ? { path: diff.path, status: diff.status, staged: false }
+      : status?.files.find((f) => f.path === comment.filePath);
     if (file) {
       selectFile(file);
       // Set the scroll target after selecting the file
@@ -63,23 +90,50 @@ export function CommentList() {
   };
 
   const handleCopyMarkdown = async () => {
-    const markdown = exportToMarkdown();
-    if (markdown) {
-      await navigator.clipboard.writeText(markdown);
+    const review = await renderReview(exportFormat, exportOptions);
+    if (review) {
+      await navigator.clipboard.writeText(review);
     }
   };
 
   const handleExportForAgent = async () => {
-    const markdown = exportToMarkdown();
-    if (!markdown || !repoPath) return;
+    if (comments.length === resolvedCount || !repoPath) return;
 
     setExportStatus("exporting");
+    setFailedDeliveries([]);
     try {
-      const outputPath = await invoke<string>("export_review", {
+      // Warn when the agent has changed code since it was commented on
+      const warnings = await invoke<StaleWarning[]>("check_review", {
+        repoPath,
+        comments,
+      });
+      if (warnings.length > 0) {
+        const proceed = await ask(
+          `${warnings.length} comment${warnings.length !== 1 ? "s" : ""} no longer match the current code:\n\n${warnings
+            .map((w) => `• ${w.message}`)
+            .join("\n")}\n\nMoved comments will be exported at their new lines and stale ones will be marked. Export anyway?`,
+          { title: "Outdated comments", kind: "warning" },
+        );
+        if (!proceed) {
+          setExportStatus("idle");
+          return;
+        }
+      }
+
+      const result = await invoke<ExportResult>("export_review", {
         repoPath,
-        markdown,
+        comments,
+        format: exportFormat,
+        options: exportOptions,
       });
-      await writeText(outputPath);
+      if (result.path) {
+        await writeText(result.path);
+      }
+      setFailedDeliveries(result.deliveries.filter((d) => !d.success));
+      if (!result.deliveries.some((d) => d.success)) {
+        setExportStatus("idle");
+        return;
+      }
       setExportStatus("exported");
       setTimeout(() => setExportStatus("idle"), 2000);
     } catch (err) {
@@ -105,6 +159,12 @@ export function CommentList() {
         <div className="flex items-center justify-between">
           <span className="text-sm font-medium text-gray-700 dark:text-gray-300">
             {comments.length} Comment{comments.length !== 1 && "s"}
+            {resolvedCount > 0 && (
+              <span className="font-normal text-gray-400 dark:text-gray-500">
+                {" "}
+                ({resolvedCount} resolved)
+              </span>
+            )}
           </span>
           <div className="flex gap-1">
             <Button variant="ghost" size="sm" onClick={handleCopyMarkdown}>
@@ -115,19 +175,66 @@ export function CommentList() {
             </Button>
           </div>
         </div>
-        <Button
-          variant="primary"
-          size="sm"
-          onClick={handleExportForAgent}
-          disabled={exportStatus !== "idle"}
-          className="w-full"
-        >
-          {exportStatus === "exporting"
-            ? "Exporting..."
-            : exportStatus === "exported"
-              ? "Exported! Path copied"
-              : "Export for Agent"}
-        </Button>
+        <div className="flex gap-1">
+          <select
+            value={exportFormat}
+            onChange={(e) => setExportFormat(e.target.value as ExportFormat)}
+            className="text-xs rounded border border-gray-200 dark:border-gray-700 bg-white dark:bg-gray-800 text-gray-700 dark:text-gray-300 px-1"
+            title="Export format"
+          >
+            {Object.entries(exportFormatLabels).map(([value, label]) => (
+              <option key={value} value={value}>
+                {label}
+              </option>
+            ))}
+          </select>
+          <Button
+            variant="primary"
+            size="sm"
+            onClick={handleExportForAgent}
+            disabled={
+              exportStatus !== "idle" || comments.length === resolvedCount
+            }
+            className="flex-1"
+          >
+            {exportStatus === "exporting"
+              ? "Exporting..."
+              : exportStatus === "exported"
+                ? "Exported!"
+                : "Export for Agent"}
+          </Button>
+        </div>
+        <div className="flex items-center justify-between gap-2">
+          <Checkbox
+            label="Include diff hunks"
+            checked={exportOptions.includeHunks}
+            onChange={(e) => setExportOptions({ includeHunks: e.target.checked })}
+          />
+          {exportOptions.includeHunks && (
+            <input
+              type="number"
+              min={0}
+              max={50}
+              value={exportOptions.hunkContextLines}
+              onChange={(e) =>
+                setExportOptions({
+                  hunkContextLines: Math.max(0, Number(e.target.value) || 0),
+                })
+              }
+              className="w-12 text-xs rounded border border-gray-200 dark:border-gray-700 bg-white dark:bg-gray-800 text-gray-700 dark:text-gray-300 px-1"
+              title="Context lines"
+            />
+          )}
+        </div>
+        {failedDeliveries.length > 0 && (
+          <ul className="space-y-1 text-xs text-red-600 dark:text-red-400">
+            {failedDeliveries.map((d) => (
+              <li key={d.destination} title={d.message} className="truncate">
+                {d.destination}: {d.message}
+              </li>
+            ))}
+          </ul>
+        )}
       </div>
 
       <div className="flex-1 overflow-y-auto p-2 space-y-3">
@@ -143,9 +250,11 @@ export function CommentList() {
                 endLine: comment.endLine,
                 codeSnippet: comment.codeSnippet,
                 isOld: comment.isOld,
+                staged: comment.staged,
                 editingId: comment.id,
                 existingContent: comment.content,
                 existingCategory: comment.category,
+                existingSuggestion: comment.suggestion,
               })
             }
             onNavigate={() => handleNavigate(comment)}
@@ -164,6 +273,7 @@ interface CommentCardProps {
 }
 
 function CommentCard({ comment, onDelete, onEdit, onNavigate }: CommentCardProps) {
+  const { setResolved } = useCommentStore();
   const lineRef =
     comment.startLine === comment.endLine
       ? `:${comment.startLine}`
@@ -179,7 +289,10 @@ function CommentCard({ comment, onDelete, onEdit, onNavigate }: CommentCardProps
   return (
     <div
       onClick={onEdit}
-      className="group bg-gray-50 dark:bg-gray-800 rounded-lg ring-1 ring-gray-200/80 dark:ring-gray-700/80 p-3 text-sm cursor-pointer hover:bg-gray-100 dark:hover:bg-gray-700 transition-colors"
+      className={clsx(
+        "group bg-gray-50 dark:bg-gray-800 rounded-lg ring-1 ring-gray-200/80 dark:ring-gray-700/80 p-3 text-sm cursor-pointer hover:bg-gray-100 dark:hover:bg-gray-700 transition-colors",
+        comment.resolved && "opacity-60",
+      )}
     >
       <div className="flex items-center justify-between gap-2">
         <div className="flex items-center gap-1.5 min-w-0">
@@ -216,6 +329,33 @@ function CommentCard({ comment, onDelete, onEdit, onNavigate }: CommentCardProps
           </span>
         </div>
         <div className="flex-shrink-0 flex gap-1">
+          <button
+            onClick={(e) => {
+              e.stopPropagation();
+              setResolved(comment, !comment.resolved);
+            }}
+            className={clsx(
+              "hover:text-green-500 dark:hover:text-green-400",
+              comment.resolved
+                ? "text-green-500 dark:text-green-400"
+                : "text-gray-400",
+            )}
+            title={comment.resolved ? "Reopen thread" : "Resolve thread"}
+          >
+            <svg
+              className="w-4 h-4"
+              fill="none"
+              viewBox="0 0 24 24"
+              stroke="currentColor"
+            >
+              <path
+                strokeLinecap="round"
+                strokeLinejoin="round"
+                strokeWidth={2}
+                d="M5 13l4 4L19 7"
+              />
+            </svg>
+          </button>
           <button
             onClick={(e) => {
               e.stopPropagation();
@@ -298,7 +438,122 @@ function CommentCard({ comment, onDelete, onEdit, onNavigate }: CommentCardProps
             <div className="pointer-events-none absolute inset-x-0 bottom-0 h-6 bg-gradient-to-t from-gray-50 group-hover:from-gray-100 dark:from-gray-800 dark:group-hover:from-gray-700 transition-colors" />
           )}
         </div>
+
+        {comment.suggestion !== undefined && (
+          <SuggestionBlock comment={comment} />
+        )}
+
+        <CommentThread comment={comment} />
       </div>
     </div>
   );
 }
+
+function SuggestionBlock({ comment }: { comment: Comment }) {
+  const { applySuggestion, undoSuggestion } = useCommentStore();
+  const { refreshStatus, fetchDiff } = useGitStore();
+  const { showFullFileContext, ignoreWhitespace } = useUiStore();
+  const applied = comment.appliedSuggestion !== undefined;
+
+  const handleToggle = async () => {
+    try {
+      if (applied) {
+        await undoSuggestion(comment);
+      } else {
+        await applySuggestion(comment);
+      }
+      await refreshStatus();
+      await fetchDiff(showFullFileContext, ignoreWhitespace);
+    } catch (err) {
+      await message(String(err), {
+        title: applied ? "Cannot undo suggestion" : "Cannot apply suggestion",
+        kind: "error",
+      });
+    }
+  };
+
+  return (
+    <div
+      onClick={(e) => e.stopPropagation()}
+      className="mt-2 rounded border border-blue-200 dark:border-blue-900 overflow-hidden cursor-default"
+    >
+      <div className="flex items-center justify-between px-2 py-1 bg-blue-50 dark:bg-blue-950/50 text-xs">
+        <span className="font-medium text-blue-700 dark:text-blue-300">
+          {applied ? "Suggestion applied" : "Suggested change"}
+        </span>
+        {!comment.isOld && (
+          <button
+            onClick={handleToggle}
+            className="text-blue-600 hover:text-blue-800 dark:text-blue-400 dark:hover:text-blue-200 font-medium"
+          >
+            {applied ? "Undo" : "Apply"}
+          </button>
+        )}
+      </div>
+      <pre className="px-2 py-1.5 font-mono text-xs text-gray-700 dark:text-gray-300 overflow-x-auto">
+        {comment.suggestion}
+      </pre>
+    </div>
+  );
+}
+
+function CommentThread({ comment }: { comment: Comment }) {
+  const { addReply, removeReply } = useCommentStore();
+  const [reply, setReply] = useState("");
+  const replies = comment.replies || [];
+
+  const handleReply = async () => {
+    if (!reply.trim()) return;
+    await addReply(comment, reply.trim());
+    setReply("");
+  };
+
+  return (
+    <div
+      onClick={(e) => e.stopPropagation()}
+      className="mt-2 pt-2 border-t border-gray-200/60 dark:border-gray-700/60 text-xs space-y-2 cursor-default"
+    >
+      {replies.map((r) => (
+        <div key={r.id} className="group/reply">
+          <div className="flex items-center gap-1.5">
+            <span className="font-medium text-gray-700 dark:text-gray-300 capitalize">
+              {r.author}
+            </span>
+            {r.status && (
+              <span
+                className={clsx(
+                  "rounded px-1.5 py-0.5 font-medium capitalize",
+                  replyStatusStyles[r.status],
+                )}
+              >
+                {r.status}
+              </span>
+            )}
+            <button
+              onClick={() => removeReply(comment, r.id)}
+              className="ml-auto opacity-0 group-hover/reply:opacity-100 text-gray-400 hover:text-red-500 dark:hover:text-red-400"
+              title="Delete reply"
+            >
+              ×
+            </button>
+          </div>
+          {r.content && (
+            <p className="mt-0.5 text-gray-600 dark:text-gray-400 leading-relaxed whitespace-pre-wrap">
+              {r.content}
+            </p>
+          )}
+        </div>
+      ))}
+      <input
+        type="text"
+        value={reply}
+        onChange={(e) => setReply(e.target.value)}
+        onKeyDown={(e) => {
+          if (e.key === "Enter") handleReply();
+        }}
+        placeholder={comment.resolved ? "Reply to reopen..." : "Reply..."}
+        className="w-full rounded border border-gray-200 dark:border-gray-700 bg-white dark:bg-gray-900 text-gray-700 dark:text-gray-300 px-2 py-1"
+      />
+    </div>
+  );
+}
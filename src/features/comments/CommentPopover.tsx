@@ -7,6 +7,9 @@ import { getLanguageFromPath } from "@/lib/syntax";
 import { stripIndent } from "@/lib/stripIndent";
 import type { CommentCategory } from "@/types/comment";
 
+// Diff lines keep their newline and are joined with another, doubling every line break
+const snippetText = (snippet: string) => snippet.replace(/\n\n/g, "\n");
+
 const categories: { value: CommentCategory; label: string; color: string }[] = [
   { value: "suggestion", label: "Suggestion", color: "bg-blue-500" },
   { value: "issue", label: "Issue", color: "bg-red-500" },
@@ -19,6 +22,7 @@ export function CommentPopover() {
   const { draft, setDraft, addComment, updateComment } = useCommentStore();
   const [content, setContent] = useState("");
   const [category, setCategory] = useState<CommentCategory>("suggestion");
+  const [suggestion, setSuggestion] = useState<string | null>(null);
 
   // Initialize state from draft when editing
   const isEditing = draft?.editingId !== undefined;
@@ -28,6 +32,8 @@ export function CommentPopover() {
     if (draft && !initializedRef.current) {
       if (draft.existingContent) setContent(draft.existingContent);
       if (draft.existingCategory) setCategory(draft.existingCategory);
+      if (draft.existingSuggestion !== undefined)
+        setSuggestion(draft.existingSuggestion);
       initializedRef.current = true;
     }
     if (!draft) {
@@ -42,7 +48,13 @@ export function CommentPopover() {
 
     if (draft.editingId) {
       // Edit existing comment
-      updateComment(draft.filePath, draft.editingId, content.trim(), category);
+      updateComment(
+        draft.filePath,
+        draft.editingId,
+        content.trim(),
+        category,
+        suggestion ?? undefined,
+      );
       setDraft(null);
     } else {
       // Create new comment
@@ -54,16 +66,20 @@ export function CommentPopover() {
         category,
         draft.codeSnippet,
         draft.isOld,
+        draft.staged,
+        suggestion ?? undefined,
       );
     }
     setContent("");
     setCategory("suggestion");
+    setSuggestion(null);
   };
 
   const handleCancel = () => {
     setDraft(null);
     setContent("");
     setCategory("suggestion");
+    setSuggestion(null);
   };
 
   return (
@@ -129,6 +145,39 @@ export function CommentPopover() {
             className="w-full h-32 px-3 py-2 border border-gray-300 dark:border-gray-600 rounded-md bg-white dark:bg-gray-900 text-gray-900 dark:text-gray-100 placeholder-gray-400 dark:placeholder-gray-500 focus:ring-2 focus:ring-blue-500 focus:border-transparent resize-none"
             autoFocus
           />
+
+          {/* Suggestions replace new-side lines, so they can't apply to deletions */}
+          {!draft.isOld &&
+            (suggestion === null ? (
+              <Button
+                variant="ghost"
+                size="sm"
+                onClick={() => setSuggestion(snippetText(draft.codeSnippet))}
+              >
+                Suggest a change
+              </Button>
+            ) : (
+              <div className="space-y-1">
+                <div className="flex items-center justify-between">
+                  <span className="text-xs font-medium text-gray-500 dark:text-gray-400">
+                    Suggested replacement
+                  </span>
+                  <Button
+                    variant="ghost"
+                    size="sm"
+                    onClick={() => setSuggestion(null)}
+                  >
+                    Remove
+                  </Button>
+                </div>
+                <textarea
+                  value={suggestion}
+                  onChange={(e) => setSuggestion(e.target.value)}
+                  spellCheck={false}
+                  className="w-full h-28 px-3 py-2 border border-gray-300 dark:border-gray-600 rounded-md bg-gray-50 dark:bg-gray-900 text-gray-900 dark:text-gray-100 font-mono text-sm focus:ring-2 focus:ring-blue-500 focus:border-transparent resize-none"
+                />
+              </div>
+            ))}
         </div>
 
         <div className="px-4 py-3 border-t border-gray-200 dark:border-gray-700 flex justify-end gap-2">
@@ -1,9 +1,14 @@
 import { clsx } from "clsx";
 import type { Language } from "prism-react-renderer";
-import type { DiffLine as DiffLineType, LineType } from "@/types/git";
+import type {
+  DiffLine as DiffLineType,
+  LineMove,
+  LineType,
+} from "@/types/git";
 import type { Comment } from "@/types/comment";
 import type { DiffSegment } from "@/lib/wordDiff";
 import { HighlightedContent } from "./HighlightedContent";
+import { MovedLink } from "./MovedLink";
 
 interface DiffLineProps {
   line: DiffLineType;
@@ -17,6 +22,7 @@ interface DiffLineProps {
   ) => void;
   onContentClick?: (comment: Comment) => void;
   onLineHover?: (lineNo: number | null) => void;
+  onMovedClick?: (move: LineMove, isOld: boolean) => void;
   showOldLineNo?: boolean;
   showNewLineNo?: boolean;
   language: Language;
@@ -34,6 +40,13 @@ const lineTypeStyles: Record<LineType, string> = {
     "bg-blue-50 dark:bg-blue-900/20 text-blue-700 dark:text-blue-300 font-medium",
 };
 
+// Moved lines are tinted apart from plain additions and deletions, like git --color-moved
+const movedLineStyles: Partial<Record<LineType, string>> = {
+  addition: "bg-cyan-100 dark:bg-cyan-900/30 text-cyan-900 dark:text-cyan-100",
+  deletion:
+    "bg-fuchsia-100 dark:bg-fuchsia-900/30 text-fuchsia-900 dark:text-fuchsia-100",
+};
+
 const lineNoPrefixStyles: Record<LineType, string> = {
   addition: "bg-green-200 dark:bg-green-900/50",
   deletion: "bg-red-200 dark:bg-red-900/50",
@@ -47,6 +60,7 @@ export function DiffLine({
   onLineClick,
   onContentClick,
   onLineHover,
+  onMovedClick,
   showOldLineNo = true,
   showNewLineNo = true,
   language,
@@ -94,7 +108,8 @@ export function DiffLine({
     <div
       className={clsx(
         "flex font-mono text-sm leading-6 group h-full",
-        lineTypeStyles[line.lineType],
+        (line.moved && movedLineStyles[line.lineType]) ||
+          lineTypeStyles[line.lineType],
         hasComments && "ring-1 ring-inset ring-yellow-400 dark:ring-yellow-600",
       )}
     >
@@ -168,6 +183,21 @@ export function DiffLine({
           diffSegments={diffSegments}
           isDark={isDark}
         />
+        {line.noNewline && (
+          <span
+            className="ml-2 text-xs text-gray-400 dark:text-gray-500 select-none"
+            title="No newline at end of file"
+          >
+            ⊘ no newline
+          </span>
+        )}
+        {line.moved && (
+          <MovedLink
+            move={line.moved}
+            isAddition={line.lineType === "addition"}
+            onClick={onMovedClick}
+          />
+        )}
       </span>
       {hasComments && (
         <span className="w-6 flex-shrink-0 text-center text-yellow-600 dark:text-yellow-400">
@@ -0,0 +1,169 @@
+import { useEffect, useRef, useState } from "react";
+import { invoke } from "@tauri-apps/api/core";
+import { Checkbox } from "@/components/ui";
+import { useGitStore } from "@/stores/gitStore";
+import { useUiStore } from "@/stores/uiStore";
+import type { DiffAlgorithm, DiffSettings } from "@/types/git";
+
+// libgit2 has no histogram diff, so git config asking for one gets patience
+const algorithmLabels: Record<Exclude<DiffAlgorithm, "histogram">, string> = {
+  myers: "Myers",
+  minimal: "Minimal",
+  patience: "Patience",
+};
+
+const algorithmLabel = (algorithm?: DiffAlgorithm) =>
+  algorithm === "histogram"
+    ? "Patience (for histogram)"
+    : algorithmLabels[algorithm ?? "myers"];
+
+// Diff options beyond the toolbar toggles. Anything left at "Git default" follows the
+// repository's git config (diff.algorithm, diff.indentHeuristic, diff.colorMoved), which
+// the backend resolves so the menu starts out showing what the diffs actually use
+export function DiffSettingsMenu() {
+  const { diffSettings, setDiffSettings } = useUiStore();
+  const { repoPath, isDemo } = useGitStore();
+  const [open, setOpen] = useState(false);
+  const [gitDefaults, setGitDefaults] = useState<DiffSettings>({});
+  const ref = useRef<HTMLDivElement>(null);
+
+  // Reread each time the menu opens, in case git config changed meanwhile
+  useEffect(() => {
+    if (!open || !repoPath || isDemo) return;
+    let current = true;
+    invoke<DiffSettings>("get_diff_settings", { repoPath })
+      .then((settings) => current && setGitDefaults(settings))
+      .catch(() => current && setGitDefaults({}));
+    return () => {
+      current = false;
+    };
+  }, [open, repoPath, isDemo]);
+
+  const indentHeuristicDefault = gitDefaults.indentHeuristic ?? true;
+  const detectMovesDefault = gitDefaults.detectMoves ?? false;
+
+  useEffect(() => {
+    if (!open) return;
+    const handleClick = (e: MouseEvent) => {
+      if (ref.current && !ref.current.contains(e.target as Node)) {
+        setOpen(false);
+      }
+    };
+    document.addEventListener("mousedown", handleClick);
+    return () => document.removeEventListener("mousedown", handleClick);
+  }, [open]);
+
+  const active =
+    diffSettings.algorithm !== undefined ||
+    diffSettings.indentHeuristic !== undefined ||
+    diffSettings.ignoreWhitespaceChange ||
+    diffSettings.ignoreWhitespaceEol ||
+    diffSettings.ignoreBlankLines ||
+    diffSettings.detectMoves !== undefined;
+
+  return (
+    <div ref={ref} className="relative">
+      <button
+        onClick={() => setOpen(!open)}
+        className={`p-1.5 rounded ${
+          active
+            ? "bg-blue-600 text-white"
+            : "text-gray-600 dark:text-gray-400 hover:bg-gray-100 dark:hover:bg-gray-700"
+        }`}
+        title="Diff options"
+      >
+        <svg
+          className="w-4 h-4"
+          fill="none"
+          viewBox="0 0 24 24"
+          stroke="currentColor"
+        >
+          <path
+            strokeLinecap="round"
+            strokeLinejoin="round"
+            strokeWidth={2}
+            d="M12 6V4m0 2a2 2 0 100 4m0-4a2 2 0 110 4m-6 8a2 2 0 100-4m0 4a2 2 0 110-4m0 4v2m0-6V4m6 6v10m6-2a2 2 0 100-4m0 4a2 2 0 110-4m0 4v2m0-6V4"
+          />
+        </svg>
+      </button>
+
+      {open && (
+        <div className="absolute right-0 top-full mt-1 z-20 w-60 p-3 space-y-2 rounded-md border border-gray-200 dark:border-gray-700 bg-white dark:bg-gray-800 shadow-lg">
+          <label className="flex items-center justify-between gap-2 text-xs text-gray-700 dark:text-gray-300">
+            Algorithm
+            <select
+              value={
+                diffSettings.algorithm === "histogram"
+                  ? "patience"
+                  : (diffSettings.algorithm ?? "")
+              }
+              onChange={(e) =>
+                setDiffSettings({
+                  algorithm: (e.target.value || undefined) as
+                    | DiffAlgorithm
+                    | undefined,
+                })
+              }
+              className="text-xs rounded border border-gray-200 dark:border-gray-700 bg-white dark:bg-gray-800 text-gray-700 dark:text-gray-300 px-1"
+            >
+              <option value="">
+                Git default ({algorithmLabel(gitDefaults.algorithm)})
+              </option>
+              {Object.entries(algorithmLabels).map(([value, label]) => (
+                <option key={value} value={value}>
+                  {label}
+                </option>
+              ))}
+            </select>
+          </label>
+          <Checkbox
+            label="Indent heuristic"
+            checked={diffSettings.indentHeuristic ?? indentHeuristicDefault}
+            onChange={(e) =>
+              setDiffSettings({
+                // Back to the git config once it matches it again
+                indentHeuristic:
+                  e.target.checked === indentHeuristicDefault
+                    ? undefined
+                    : e.target.checked,
+              })
+            }
+          />
+          <Checkbox
+            label="Ignore whitespace at line end"
+            checked={diffSettings.ignoreWhitespaceEol ?? false}
+            onChange={(e) =>
+              setDiffSettings({ ignoreWhitespaceEol: e.target.checked })
+            }
+          />
+          <Checkbox
+            label="Ignore changes in amount of whitespace"
+            checked={diffSettings.ignoreWhitespaceChange ?? false}
+            onChange={(e) =>
+              setDiffSettings({ ignoreWhitespaceChange: e.target.checked })
+            }
+          />
+          <Checkbox
+            label="Ignore blank lines"
+            checked={diffSettings.ignoreBlankLines ?? false}
+            onChange={(e) =>
+              setDiffSettings({ ignoreBlankLines: e.target.checked })
+            }
+          />
+          <Checkbox
+            label="Detect moved code"
+            checked={diffSettings.detectMoves ?? detectMovesDefault}
+            onChange={(e) =>
+              setDiffSettings({
+                detectMoves:
+                  e.target.checked === detectMovesDefault
+                    ? undefined
+                    : e.target.checked,
+              })
+            }
+          />
+        </div>
+      )}
+    </div>
+  );
+}
@@ -1,13 +1,25 @@
-import { useCallback, useEffect, useState } from "react";
+import { useCallback, useEffect, useMemo, useState } from "react";
 import { useGitStore } from "@/stores/gitStore";
 import { useCommentStore } from "@/stores/commentStore";
 import { useUiStore } from "@/stores/uiStore";
 import { UnifiedDiffView } from "./UnifiedDiffView";
 import { SplitDiffView } from "./SplitDiffView";
+import { LargeDiffNotice } from "./LargeDiffNotice";
+import { DiffSettingsMenu } from "./DiffSettingsMenu";
 import type { Comment } from "@/types/comment";
+import type { LineMove } from "@/types/git";
 
 export function DiffViewer() {
-  const { currentDiff, selectedFile, fetchDiff } = useGitStore();
+  const {
+    currentDiff,
+    selectedFile,
+    status,
+    patchPreview,
+    selectFile,
+    fetchDiff,
+    loadHunk,
+    loadFullDiff,
+  } = useGitStore();
   const { getFileComments, setDraft } = useCommentStore();
   const {
     diffViewMode,
@@ -16,6 +28,7 @@ export function DiffViewer() {
     setShowFullFileContext,
     ignoreWhitespace,
     setIgnoreWhitespace,
+    diffSettings,
     scrollToLine,
     setScrollToLine,
   } = useUiStore();
@@ -28,11 +41,29 @@ export function DiffViewer() {
 
   const comments = currentDiff ? getFileComments(currentDiff.path) : [];
 
+  // A large diff only shows the hunks that have been loaded so far
+  const shownDiff = useMemo(
+    () =>
+      currentDiff?.isLarge
+        ? {
+            ...currentDiff,
+            hunks: currentDiff.hunks.filter((h) => h.lines.length > 0),
+          }
+        : currentDiff,
+    [currentDiff],
+  );
+
   useEffect(() => {
     if (selectedFile) {
       fetchDiff(showFullFileContext, ignoreWhitespace);
     }
-  }, [showFullFileContext, ignoreWhitespace, fetchDiff, selectedFile]);
+  }, [
+    showFullFileContext,
+    ignoreWhitespace,
+    diffSettings,
+    fetchDiff,
+    selectedFile,
+  ]);
 
   const handleLineClick = useCallback(
     (lineNo: number, isOld: boolean, content: string, shiftKey: boolean) => {
@@ -79,6 +110,7 @@ export function DiffViewer() {
             endLine,
             codeSnippet: snippetLines.join("\n"),
             isOld: rangeStart.isOld,
+            staged: selectedFile?.staged,
           });
           setRangeStart(null);
         } else {
@@ -94,10 +126,11 @@ export function DiffViewer() {
           endLine: lineNo,
           codeSnippet: content,
           isOld,
+          staged: selectedFile?.staged,
         });
       }
     },
-    [currentDiff, setDraft, rangeStart],
+    [currentDiff, selectedFile, setDraft, rangeStart],
   );
 
   const handleLineHover = useCallback((lineNo: number | null) => {
@@ -112,15 +145,43 @@ export function DiffViewer() {
         endLine: comment.endLine,
         codeSnippet: comment.codeSnippet,
         isOld: comment.isOld,
+        staged: comment.staged,
         editingId: comment.id,
         existingContent: comment.content,
         existingCategory: comment.category,
+        existingSuggestion: comment.suggestion,
       });
     },
     [setDraft],
   );
 
-  if (!currentDiff || !selectedFile) {
+  // Jumps to the other end of a moved line, which may be in another file
+  const handleMovedClick = useCallback(
+    (move: LineMove, isOld: boolean) => {
+      if (move.path !== selectedFile?.path) {
+        const diff = patchPreview?.files.find((f) => f.path === move.path);
+        const candidates = status?.files.filter((f) => f.path === move.path);
+        const file = diff
+          ? { path: diff.path, status: diff.status, staged: false }
+          : (candidates?.find((f) => f.staged === selectedFile?.staged) ??
+            candidates?.[0]);
+        if (!file) return;
+        selectFile(file, showFullFileContext, ignoreWhitespace);
+      }
+      setScrollToLine({ line: move.line, isOld });
+    },
+    [
+      selectedFile,
+      patchPreview,
+      status,
+      selectFile,
+      showFullFileContext,
+      ignoreWhitespace,
+      setScrollToLine,
+    ],
+  );
+
+  if (!currentDiff || !shownDiff || !selectedFile) {
     return (
       <div className="h-full flex items-center justify-center text-gray-500 dark:text-gray-400 bg-white dark:bg-gray-900">
         <p className="text-sm">Select a file to view diff</p>
@@ -140,6 +201,36 @@ export function DiffViewer() {
               (renamed from {currentDiff.oldPath})
             </span>
           )}
+          {currentDiff.encoding &&
+            (currentDiff.encoding !== "UTF-8" || currentDiff.hasBom) && (
+              <span className="px-1.5 py-0.5 text-xs bg-gray-100 dark:bg-gray-800 text-gray-600 dark:text-gray-300 rounded">
+                {currentDiff.encoding}
+                {currentDiff.hasBom && " with BOM"}
+              </span>
+            )}
+          {currentDiff.oldMode !== undefined &&
+            currentDiff.newMode !== undefined &&
+            currentDiff.oldMode !== currentDiff.newMode && (
+              <span className="px-1.5 py-0.5 text-xs bg-gray-100 dark:bg-gray-800 text-gray-600 dark:text-gray-300 rounded font-mono">
+                {currentDiff.oldMode.toString(8)} →{" "}
+                {currentDiff.newMode.toString(8)}
+              </span>
+            )}
+          {(currentDiff.oldLinkTarget || currentDiff.newLinkTarget) && (
+            <span className="text-xs text-gray-500 dark:text-gray-400 font-mono">
+              symlink{" "}
+              {currentDiff.oldLinkTarget &&
+              currentDiff.newLinkTarget &&
+              currentDiff.oldLinkTarget !== currentDiff.newLinkTarget
+                ? `${currentDiff.oldLinkTarget} → ${currentDiff.newLinkTarget}`
+                : `→ ${currentDiff.newLinkTarget ?? currentDiff.oldLinkTarget}`}
+            </span>
+          )}
+          {currentDiff.lineEndingsOnly && (
+            <span className="px-1.5 py-0.5 text-xs bg-amber-100 dark:bg-amber-900/40 text-amber-700 dark:text-amber-300 rounded">
+              Line endings only
+            </span>
+          )}
         </div>
 
         <div className="flex items-center gap-2">
@@ -224,17 +315,37 @@ export function DiffViewer() {
               />
             </svg>
           </button>
+          <DiffSettingsMenu />
         </div>
       </div>
 
+      {currentDiff.isLarge && (
+        <LargeDiffNotice
+          diff={currentDiff}
+          onLoadHunk={(index) =>
+            loadHunk(index, showFullFileContext, ignoreWhitespace)
+          }
+          onLoadAll={() => loadFullDiff(showFullFileContext, ignoreWhitespace)}
+        />
+      )}
+
       <div className="flex-1 min-h-0">
-        {diffViewMode === "unified" ? (
+        {currentDiff.isLarge && shownDiff.hunks.length === 0 ? (
+          <div className="h-full flex items-center justify-center text-gray-500 dark:text-gray-400">
+            <p className="text-sm">
+              {currentDiff.hunks.length === 0
+                ? "File too large to display"
+                : "Large diff collapsed - load hunks above"}
+            </p>
+          </div>
+        ) : diffViewMode === "unified" ? (
           <UnifiedDiffView
-            diff={currentDiff}
+            diff={shownDiff}
             comments={comments}
             onLineClick={handleLineClick}
             onContentClick={handleContentClick}
             onLineHover={handleLineHover}
+            onMovedClick={handleMovedClick}
             rangeSelectionStart={rangeStart?.lineNo ?? null}
             rangeSelectionIsOld={rangeStart?.isOld ?? null}
             hoveredLine={rangeStart ? hoveredLine : null}
@@ -243,11 +354,12 @@ export function DiffViewer() {
           />
         ) : (
           <SplitDiffView
-            diff={currentDiff}
+            diff={shownDiff}
             comments={comments}
             onLineClick={handleLineClick}
             onContentClick={handleContentClick}
             onLineHover={handleLineHover}
+            onMovedClick={handleMovedClick}
             rangeSelectionStart={rangeStart?.lineNo ?? null}
             rangeSelectionIsOld={rangeStart?.isOld ?? null}
             hoveredLine={rangeStart ? hoveredLine : null}
@@ -0,0 +1,56 @@
+import type { FileDiff } from "@/types/git";
+
+interface LargeDiffNoticeProps {
+  diff: FileDiff;
+  onLoadHunk: (hunkIndex: number) => void;
+  onLoadAll: () => void;
+}
+
+// Shown instead of the full diff for files over the size limits, so each hunk is only
+// rendered once it is asked for, a page of lines at a time
+export function LargeDiffNotice({
+  diff,
+  onLoadHunk,
+  onLoadAll,
+}: LargeDiffNoticeProps) {
+  const pending = diff.hunks.filter((h) => (h.omittedLines ?? 0) > 0).length;
+  if (pending === 0) return null;
+
+  return (
+    <div className="flex-shrink-0 max-h-64 overflow-auto border-b border-gray-200 dark:border-gray-700 bg-amber-50 dark:bg-amber-900/20">
+      <div className="flex items-center justify-between px-4 py-2">
+        <span className="text-xs text-amber-800 dark:text-amber-200">
+          Large diff collapsed: {pending} of {diff.hunks.length} hunk
+          {diff.hunks.length !== 1 ? "s" : ""} not fully loaded
+        </span>
+        <button
+          onClick={onLoadAll}
+          className="px-2 py-1 text-xs rounded bg-amber-600 text-white hover:bg-amber-700"
+        >
+          Load entire diff
+        </button>
+      </div>
+      <ul className="px-4 pb-2 space-y-1">
+        {diff.hunks.map((hunk, index) =>
+          (hunk.omittedLines ?? 0) > 0 ? (
+            <li key={index} className="flex items-center gap-2">
+              <button
+                onClick={() => onLoadHunk(index)}
+                className="px-1.5 py-0.5 text-xs rounded border border-amber-400 dark:border-amber-600 text-amber-800 dark:text-amber-200 hover:bg-amber-100 dark:hover:bg-amber-900/40"
+              >
+                {hunk.lines.length === 0 ? "Load" : "Load more"}
+              </button>
+              <code className="text-xs text-gray-600 dark:text-gray-400 truncate">
+                {hunk.header}
+              </code>
+              <span className="text-xs text-gray-500">
+                −{hunk.oldLines} +{hunk.newLines}
+                {hunk.lines.length > 0 && ` (${hunk.omittedLines} lines left)`}
+              </span>
+            </li>
+          ) : null,
+        )}
+      </ul>
+    </div>
+  );
+}
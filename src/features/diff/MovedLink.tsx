@@ -0,0 +1,25 @@
+import type { LineMove } from "@/types/git";
+
+interface MovedLinkProps {
+  move: LineMove;
+  // Whether the line is an addition, so its counterpart is the deleted line
+  isAddition: boolean;
+  onClick?: (move: LineMove, isOld: boolean) => void;
+}
+
+// Names the other end of a moved line and jumps to it when clicked
+export function MovedLink({ move, isAddition, onClick }: MovedLinkProps) {
+  return (
+    <button
+      onClick={(e) => {
+        e.stopPropagation();
+        onClick?.(move, isAddition);
+      }}
+      className="ml-3 text-xs text-gray-500 dark:text-gray-400 hover:underline select-none"
+      title={`Go to ${move.path}:${move.line}`}
+    >
+      {isAddition ? "moved from" : "moved to"} {move.path}:{move.line}
+      {move.edited && " (edited)"}
+    </button>
+  );
+}
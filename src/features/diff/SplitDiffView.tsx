@@ -1,13 +1,18 @@
 import { useRef, useMemo, useEffect, useCallback } from "react";
 import { useVirtualizer } from "@tanstack/react-virtual";
 import { clsx } from "clsx";
-import type { FileDiff, DiffLine as DiffLineType } from "@/types/git";
+import type {
+  FileDiff,
+  DiffLine as DiffLineType,
+  LineMove,
+} from "@/types/git";
 import type { Comment } from "@/types/comment";
 import type { DiffSegment } from "@/lib/wordDiff";
 import type { ScrollToLine } from "@/stores/uiStore";
 import { computeWordDiff, mergeSegments } from "@/lib/wordDiff";
 import { getLanguageFromPath } from "@/lib/syntax";
 import { HighlightedContent } from "./HighlightedContent";
+import { MovedLink } from "./MovedLink";
 
 interface SplitDiffViewProps {
   diff: FileDiff;
@@ -20,6 +25,7 @@ interface SplitDiffViewProps {
   ) => void;
   onContentClick: (comment: Comment) => void;
   onLineHover: (lineNo: number | null) => void;
+  onMovedClick?: (move: LineMove, isOld: boolean) => void;
   rangeSelectionStart?: number | null;
   rangeSelectionIsOld?: boolean | null;
   hoveredLine?: number | null;
@@ -42,6 +48,7 @@ export function SplitDiffView({
   onLineClick,
   onContentClick,
   onLineHover,
+  onMovedClick,
   rangeSelectionStart,
   rangeSelectionIsOld,
   hoveredLine,
@@ -289,6 +296,7 @@ export function SplitDiffView({
                     onLineClick={onLineClick}
                     onContentClick={onContentClick}
                     onLineHover={onLineHover}
+                    onMovedClick={onMovedClick}
                     language={language}
                     diffSegments={row.leftDiffSegments}
                     isRangeSelectionStart={
@@ -370,6 +378,7 @@ export function SplitDiffView({
                   onLineClick={onLineClick}
                   onContentClick={onContentClick}
                   onLineHover={onLineHover}
+                  onMovedClick={onMovedClick}
                   language={language}
                   diffSegments={row.rightDiffSegments}
                   isRangeSelectionStart={
@@ -408,6 +417,7 @@ interface SplitSideProps {
   ) => void;
   onContentClick: (comment: Comment) => void;
   onLineHover: (lineNo: number | null) => void;
+  onMovedClick?: (move: LineMove, isOld: boolean) => void;
   language: ReturnType<typeof getLanguageFromPath>;
   diffSegments?: DiffSegment[];
   isRangeSelectionStart?: boolean;
@@ -421,6 +431,7 @@ function SplitSide({
   onLineClick,
   onContentClick,
   onLineHover,
+  onMovedClick,
   language,
   diffSegments,
   isRangeSelectionStart = false,
@@ -458,11 +469,16 @@ function SplitSide({
     }
   };
 
+  // Moved lines are tinted apart from plain changes, like git --color-moved
   const bgColor =
     line.lineType === "addition"
-      ? "bg-green-100 dark:bg-green-900/30"
+      ? line.moved
+        ? "bg-cyan-100 dark:bg-cyan-900/30"
+        : "bg-green-100 dark:bg-green-900/30"
       : line.lineType === "deletion"
-        ? "bg-red-100 dark:bg-red-900/30"
+        ? line.moved
+          ? "bg-fuchsia-100 dark:bg-fuchsia-900/30"
+          : "bg-red-100 dark:bg-red-900/30"
         : "";
 
   const lineNoBg =
@@ -546,6 +562,21 @@ function SplitSide({
           diffSegments={diffSegments}
           isDark={isDark}
         />
+        {line.noNewline && (
+          <span
+            className="ml-2 text-xs text-gray-400 dark:text-gray-500 select-none"
+            title="No newline at end of file"
+          >
+            ⊘ no newline
+          </span>
+        )}
+        {line.moved && (
+          <MovedLink
+            move={line.moved}
+            isAddition={line.lineType === "addition"}
+            onClick={onMovedClick}
+          />
+        )}
       </span>
 
       {hasComments && (
@@ -1,6 +1,6 @@
 import { useRef, useMemo, useEffect } from "react";
 import { useVirtualizer } from "@tanstack/react-virtual";
-import type { FileDiff } from "@/types/git";
+import type { FileDiff, LineMove } from "@/types/git";
 import type { Comment } from "@/types/comment";
 import type { DiffSegment } from "@/lib/wordDiff";
 import type { ScrollToLine } from "@/stores/uiStore";
@@ -19,6 +19,7 @@ interface UnifiedDiffViewProps {
   ) => void;
   onContentClick: (comment: Comment) => void;
   onLineHover: (lineNo: number | null) => void;
+  onMovedClick?: (move: LineMove, isOld: boolean) => void;
   rangeSelectionStart?: number | null;
   rangeSelectionIsOld?: boolean | null;
   hoveredLine?: number | null;
@@ -41,6 +42,7 @@ export function UnifiedDiffView({
   onLineClick,
   onContentClick,
   onLineHover,
+  onMovedClick,
   rangeSelectionStart,
   rangeSelectionIsOld,
   hoveredLine,
@@ -216,6 +218,7 @@ export function UnifiedDiffView({
                 onLineClick={onLineClick}
                 onContentClick={onContentClick}
                 onLineHover={onLineHover}
+                onMovedClick={onMovedClick}
                 language={language}
                 diffSegments={item.diffSegments}
                 isRangeSelectionStart={
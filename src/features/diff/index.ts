@@ -2,3 +2,6 @@ export { DiffViewer } from "./DiffViewer";
 export { UnifiedDiffView } from "./UnifiedDiffView";
 export { SplitDiffView } from "./SplitDiffView";
 export { DiffLine } from "./DiffLine";
+export { LargeDiffNotice } from "./LargeDiffNotice";
+export { DiffSettingsMenu } from "./DiffSettingsMenu";
+export { MovedLink } from "./MovedLink";
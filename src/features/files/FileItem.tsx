@@ -6,7 +6,8 @@ interface FileItemProps {
   file: FileEntry;
   isSelected: boolean;
   onSelect: () => void;
-  onStageToggle: () => void;
+  // Omitted where the file can't be staged, e.g. in a patch preview
+  onStageToggle?: () => void;
   commentCount: number;
 }
 
@@ -43,14 +44,16 @@ export function FileItem({
       )}
       onClick={onSelect}
     >
-      <div
-        onClick={(e) => {
-          e.stopPropagation();
-          onStageToggle();
-        }}
-      >
-        <Checkbox checked={file.staged} onChange={() => {}} />
-      </div>
+      {onStageToggle && (
+        <div
+          onClick={(e) => {
+            e.stopPropagation();
+            onStageToggle();
+          }}
+        >
+          <Checkbox checked={file.staged} onChange={() => {}} />
+        </div>
+      )}
 
       <span
         className={clsx("font-mono text-xs font-bold w-4 text-center", color)}
@@ -13,9 +13,9 @@ export function FileList() {
     unstageFile,
     stageAll,
     unstageAll,
+    comparison,
   } = useGitStore();
   const comments = useCommentStore((state) => state.comments);
-  const currentRepoPath = useCommentStore((state) => state.currentRepoPath);
 
   const { stagedFiles, unstagedFiles } = useMemo(() => {
     if (!status) return { stagedFiles: [], unstagedFiles: [] };
@@ -28,13 +28,11 @@ export function FileList() {
 
   const commentCountByFile = useMemo(() => {
     const counts: Record<string, number> = {};
-    if (!currentRepoPath) return counts;
-    const repoComments = comments[currentRepoPath] || {};
-    for (const [filePath, fileComments] of Object.entries(repoComments)) {
+    for (const [filePath, fileComments] of Object.entries(comments)) {
       counts[filePath] = fileComments.length;
     }
     return counts;
-  }, [comments, currentRepoPath]);
+  }, [comments]);
 
   const handleStageToggle = (file: (typeof stagedFiles)[0]) => {
     if (file.staged) {
@@ -96,9 +94,12 @@ export function FileList() {
             <span className="text-xs font-medium text-gray-600 dark:text-gray-400 uppercase tracking-wide">
               Changes ({unstagedFiles.length})
             </span>
-            <Button variant="ghost" size="sm" onClick={stageAll}>
-              Stage All
-            </Button>
+            {/* Compared directories have no index to stage into */}
+            {!comparison && (
+              <Button variant="ghost" size="sm" onClick={stageAll}>
+                Stage All
+              </Button>
+            )}
           </div>
           <div className="flex-1 overflow-y-auto py-1">
             {unstagedFiles.map((file) => (
@@ -110,7 +111,9 @@ export function FileList() {
                   selectedFile?.staged === file.staged
                 }
                 onSelect={() => selectFile(file)}
-                onStageToggle={() => handleStageToggle(file)}
+                onStageToggle={
+                  comparison ? undefined : () => handleStageToggle(file)
+                }
                 commentCount={commentCountByFile[file.path] || 0}
               />
             ))}
@@ -0,0 +1,191 @@
+import { useCallback, useEffect, useState } from "react";
+import { invoke } from "@tauri-apps/api/core";
+import { ask } from "@tauri-apps/plugin-dialog";
+import { useGitStore } from "@/stores/gitStore";
+import { Button } from "@/components/ui";
+import {
+  exportFormatLabels,
+  type ExportResult,
+  type ReviewHistoryEntry,
+  type ReviewMetadata,
+  type ReviewSummary,
+} from "@/types/review";
+
+function formatSummary(summary: ReviewSummary): string {
+  const parts = [
+    [summary.issues, "issue"],
+    [summary.suggestions, "suggestion"],
+    [summary.questions, "question"],
+    [summary.nitpicks, "nitpick"],
+    [summary.praise, "praise"],
+  ] as const;
+  return parts
+    .filter(([count]) => count > 0)
+    .map(
+      ([count, label]) =>
+        `${count} ${label}${count !== 1 && label !== "praise" ? "s" : ""}`,
+    )
+    .join(", ");
+}
+
+export function ReviewHistory() {
+  const { repoPath } = useGitStore();
+  const [reviews, setReviews] = useState<ReviewMetadata[]>([]);
+  const [selected, setSelected] = useState<ReviewHistoryEntry | null>(null);
+  const [keep, setKeep] = useState(20);
+  const [message, setMessage] = useState<string | null>(null);
+
+  const refresh = useCallback(async () => {
+    if (!repoPath) return;
+    try {
+      setReviews(
+        await invoke<ReviewMetadata[]>("list_review_history", { repoPath }),
+      );
+    } catch (err) {
+      console.error("Failed to load review history:", err);
+    }
+  }, [repoPath]);
+
+  useEffect(() => {
+    refresh();
+  }, [refresh]);
+
+  const handleOpen = async (id: string) => {
+    try {
+      setSelected(
+        await invoke<ReviewHistoryEntry>("open_review_history", { id }),
+      );
+    } catch (err) {
+      console.error("Failed to open review:", err);
+    }
+  };
+
+  const handleReexport = async (id: string) => {
+    try {
+      const result = await invoke<ExportResult>("reexport_review", { id });
+      const failed = result.deliveries.filter((d) => !d.success);
+      setMessage(
+        failed.length === 0
+          ? "Exported again"
+          : failed.map((d) => `${d.destination}: ${d.message}`).join("\n"),
+      );
+      await refresh();
+    } catch (err) {
+      setMessage(String(err));
+    }
+  };
+
+  const handlePrune = async () => {
+    if (!repoPath) return;
+    const proceed = await ask(
+      `Delete all but the ${keep} most recent exports of this repository?`,
+      { title: "Prune review history", kind: "warning" },
+    );
+    if (!proceed) return;
+    try {
+      const removed = await invoke<number>("prune_review_history", {
+        repoPath,
+        keep,
+      });
+      setMessage(`Removed ${removed} export${removed !== 1 ? "s" : ""}`);
+      await refresh();
+    } catch (err) {
+      setMessage(String(err));
+    }
+  };
+
+  if (selected) {
+    return (
+      <div className="h-full flex flex-col">
+        <div className="flex-shrink-0 px-3 py-2 border-b border-gray-200 dark:border-gray-700 flex items-center justify-between">
+          <Button variant="ghost" size="sm" onClick={() => setSelected(null)}>
+            Back
+          </Button>
+          <Button
+            variant="primary"
+            size="sm"
+            onClick={() => handleReexport(selected.id)}
+          >
+            Re-export
+          </Button>
+        </div>
+        <pre className="flex-1 overflow-auto p-3 text-xs font-mono whitespace-pre-wrap text-gray-700 dark:text-gray-300">
+          {selected.content ??
+            selected.comments
+              .map(
+                (c) =>
+                  `${c.filePath}:${c.startLine}-${c.endLine} [${c.category}]\n${c.content}`,
+              )
+              .join("\n\n")}
+        </pre>
+      </div>
+    );
+  }
+
+  return (
+    <div className="h-full flex flex-col">
+      <div className="flex-shrink-0 px-3 py-2 border-b border-gray-200 dark:border-gray-700 space-y-2">
+        <div className="flex items-center justify-between">
+          <span className="text-sm font-medium text-gray-700 dark:text-gray-300">
+            {reviews.length} Past Review{reviews.length !== 1 && "s"}
+          </span>
+          <div className="flex items-center gap-1">
+            <input
+              type="number"
+              min={0}
+              value={keep}
+              onChange={(e) => setKeep(Math.max(0, Number(e.target.value) || 0))}
+              className="w-12 text-xs rounded border border-gray-200 dark:border-gray-700 bg-white dark:bg-gray-800 text-gray-700 dark:text-gray-300 px-1"
+              title="Number of exports to keep"
+            />
+            <Button
+              variant="ghost"
+              size="sm"
+              onClick={handlePrune}
+              disabled={reviews.length <= keep}
+            >
+              Prune
+            </Button>
+          </div>
+        </div>
+        {message && (
+          <p className="text-xs text-gray-500 dark:text-gray-400 whitespace-pre-line">
+            {message}
+          </p>
+        )}
+      </div>
+
+      <div className="flex-1 overflow-y-auto p-2 space-y-2">
+        {reviews.length === 0 && (
+          <p className="text-sm text-center text-gray-500 dark:text-gray-400 p-4">
+            No exported reviews yet.
+          </p>
+        )}
+        {reviews.map((review) => (
+          <div
+            key={review.id}
+            onClick={() => handleOpen(review.id)}
+            className="bg-gray-50 dark:bg-gray-800 rounded-lg ring-1 ring-gray-200/80 dark:ring-gray-700/80 p-3 text-xs cursor-pointer hover:bg-gray-100 dark:hover:bg-gray-700 transition-colors"
+          >
+            <div className="flex items-center justify-between gap-2">
+              <span className="font-medium text-gray-900 dark:text-gray-100">
+                {new Date(review.createdAt).toLocaleString()}
+              </span>
+              <span className="text-gray-400 dark:text-gray-500">
+                {exportFormatLabels[review.format]}
+              </span>
+            </div>
+            <div className="mt-1 text-gray-500 dark:text-gray-400 truncate">
+              {review.branch ?? "detached"}
+              {review.head && ` @ ${review.head.slice(0, 7)}`}
+            </div>
+            <div className="mt-1 text-gray-700 dark:text-gray-300">
+              {review.summary.total} comment{review.summary.total !== 1 && "s"}
+              {review.summary.total > 0 && `: ${formatSummary(review.summary)}`}
+            </div>
+          </div>
+        ))}
+      </div>
+    </div>
+  );
+}
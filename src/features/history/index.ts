@@ -0,0 +1 @@
+export { ReviewHistory } from "./ReviewHistory";
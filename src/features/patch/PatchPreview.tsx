@@ -0,0 +1,119 @@
+import { useState } from "react";
+import { useGitStore } from "@/stores/gitStore";
+import { useCommentStore } from "@/stores/commentStore";
+import { Button } from "@/components/ui";
+import { FileItem } from "@/features/files";
+import type { PatchConflict, PatchTarget } from "@/types/git";
+
+export function PatchPreview() {
+  const {
+    patchPreview,
+    patchName,
+    selectedFile,
+    selectFile,
+    applyPatch,
+    closePatch,
+  } = useGitStore();
+  const comments = useCommentStore((state) => state.comments);
+  const [conflicts, setConflicts] = useState<PatchConflict[]>([]);
+  const [error, setError] = useState<string | null>(null);
+  const [isApplying, setIsApplying] = useState(false);
+
+  if (!patchPreview) return null;
+
+  const handleApply = async (target: PatchTarget) => {
+    setIsApplying(true);
+    setError(null);
+    try {
+      const result = await applyPatch(target);
+      setConflicts(result.conflicts);
+    } catch (err) {
+      setError(String(err));
+    } finally {
+      setIsApplying(false);
+    }
+  };
+
+  return (
+    <div className="h-full flex flex-col">
+      <div className="flex-shrink-0 px-2 py-1.5 bg-gray-50 dark:bg-gray-800/50">
+        <div className="flex items-center justify-between">
+          <span className="text-xs font-medium text-gray-600 dark:text-gray-400 uppercase tracking-wide">
+            {patchName ?? "Patch"} ({patchPreview.files.length})
+          </span>
+          {/* Without a repository there is nothing to close back to or apply to */}
+          {!patchName && (
+            <Button variant="ghost" size="sm" onClick={closePatch}>
+              Close
+            </Button>
+          )}
+        </div>
+        {!patchName && (
+          <div className="flex gap-1 mt-1">
+            <Button
+              variant="primary"
+              size="sm"
+              onClick={() => handleApply("workdir")}
+              disabled={isApplying}
+            >
+              Apply
+            </Button>
+            <Button
+              variant="secondary"
+              size="sm"
+              onClick={() => handleApply("index")}
+              disabled={isApplying}
+            >
+              Apply to Index
+            </Button>
+            <Button
+              variant="secondary"
+              size="sm"
+              onClick={() => handleApply("both")}
+              disabled={isApplying}
+            >
+              Both
+            </Button>
+          </div>
+        )}
+      </div>
+
+      {(conflicts.length > 0 || error) && (
+        <div className="flex-shrink-0 px-2 py-1.5 text-xs text-red-600 dark:text-red-400 border-b border-gray-200 dark:border-gray-700">
+          {error && <p>{error}</p>}
+          {conflicts.length > 0 && (
+            <>
+              <p className="font-medium">
+                Not applied - {conflicts.length} conflict
+                {conflicts.length !== 1 && "s"}:
+              </p>
+              <ul className="mt-1 space-y-1">
+                {conflicts.map((conflict, i) => (
+                  <li key={i} className="font-mono break-all">
+                    {conflict.path}
+                    {conflict.hunk && ` ${conflict.hunk}`}: {conflict.message}
+                  </li>
+                ))}
+              </ul>
+            </>
+          )}
+        </div>
+      )}
+
+      <div className="flex-1 overflow-y-auto py-1">
+        {patchPreview.files.map((diff) => {
+          const file = { path: diff.path, status: diff.status, staged: false };
+          return (
+            <FileItem
+              key={diff.path}
+              file={file}
+              isSelected={selectedFile?.path === diff.path}
+              onSelect={() => selectFile(file)}
+              commentCount={comments[diff.path]?.length || 0}
+            />
+          );
+        })}
+      </div>
+    </div>
+  );
+}
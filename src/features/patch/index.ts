@@ -0,0 +1 @@
+export { PatchPreview } from "./PatchPreview";
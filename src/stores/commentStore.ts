@@ -1,14 +1,24 @@
 import { create } from "zustand";
-import { persist } from "zustand/middleware";
-import type { Comment, CommentCategory, CommentDraft } from "@/types/comment";
-import { getLanguageFromPath } from "@/lib/syntax";
+import { invoke } from "@tauri-apps/api/core";
+import type {
+  Comment,
+  CommentCategory,
+  CommentDraft,
+  CommentReply,
+} from "@/types/comment";
+import type { ExportFormat, ExportOptions } from "@/types/review";
+
+// Comments used to be persisted here by zustand; they now live in the backend
+const LEGACY_STORAGE_KEY = "revu-comments";
 
 interface CommentState {
   currentRepoPath: string | null;
-  comments: Record<string, Record<string, Comment[]>>; // repoPath -> filePath -> comments
+  comments: Record<string, Comment[]>; // filePath -> comments for the current repo
   draft: CommentDraft | null;
+  isDemo: boolean;
 
-  setRepoPath: (repoPath: string) => void;
+  setRepoPath: (repoPath: string) => Promise<void>;
+  loadComments: () => Promise<void>;
   addComment: (
     filePath: string,
     startLine: number,
@@ -17,223 +27,352 @@ interface CommentState {
     category: CommentCategory,
     codeSnippet: string,
     isOld: boolean,
-  ) => void;
-  removeComment: (filePath: string, commentId: string) => void;
+    staged?: boolean,
+    suggestion?: string,
+  ) => Promise<void>;
+  removeComment: (filePath: string, commentId: string) => Promise<void>;
   updateComment: (
     filePath: string,
     commentId: string,
     content: string,
     category: CommentCategory,
-  ) => void;
+    suggestion?: string,
+  ) => Promise<void>;
+  /** Writes the comment's suggestion to the working tree; throws if it no longer fits. */
+  applySuggestion: (comment: Comment) => Promise<void>;
+  undoSuggestion: (comment: Comment) => Promise<void>;
+  addReply: (comment: Comment, content: string) => Promise<void>;
+  removeReply: (comment: Comment, replyId: string) => Promise<void>;
+  setResolved: (comment: Comment, resolved: boolean) => Promise<void>;
   getFileComments: (filePath: string) => Comment[];
   getAllComments: () => Comment[];
   setDraft: (draft: CommentDraft | null) => void;
-  clearAllComments: () => void;
-  exportToMarkdown: () => string;
+  clearAllComments: () => Promise<void>;
+  renderReview: (
+    format?: ExportFormat,
+    options?: ExportOptions,
+  ) => Promise<string>;
   // Demo mode - accepts pre-built comments
   initDemoComments: (repoPath: string, comments: Record<string, Comment[]>) => void;
 }
 
-export const useCommentStore = create<CommentState>()(
-  persist(
-    (set, get) => ({
-      currentRepoPath: null,
-      comments: {},
-      draft: null,
+function groupByFile(comments: Comment[]): Record<string, Comment[]> {
+  const grouped: Record<string, Comment[]> = {};
+  for (const comment of comments) {
+    (grouped[comment.filePath] ||= []).push(comment);
+  }
+  return grouped;
+}
+
+/** Moves any comments left in localStorage for this repo into the backend store. */
+async function migrateLegacyComments(repoPath: string) {
+  const raw = localStorage.getItem(LEGACY_STORAGE_KEY);
+  if (!raw) return;
+
+  const legacy = JSON.parse(raw) as {
+    state?: { comments?: Record<string, Record<string, Comment[]>> };
+  };
+  const repoComments = legacy.state?.comments?.[repoPath];
+  if (!repoComments) return;
+
+  await invoke<number>("import_comments", {
+    repoPath,
+    comments: Object.values(repoComments).flat(),
+  });
+
+  delete legacy.state!.comments![repoPath];
+  if (Object.keys(legacy.state!.comments!).length === 0) {
+    localStorage.removeItem(LEGACY_STORAGE_KEY);
+  } else {
+    localStorage.setItem(LEGACY_STORAGE_KEY, JSON.stringify(legacy));
+  }
+}
+
+export const useCommentStore = create<CommentState>()((set, get) => {
+  /** Swaps in the backend's copy of a comment after a thread change. */
+  const replaceComment = (updated: Comment) =>
+    set((state) => ({
+      comments: {
+        ...state.comments,
+        [updated.filePath]: (state.comments[updated.filePath] || []).map((c) =>
+          c.id === updated.id ? updated : c,
+        ),
+      },
+    }));
+
+  return {
+    currentRepoPath: null,
+    comments: {},
+    draft: null,
+    isDemo: false,
+
+    setRepoPath: async (repoPath) => {
+      set({ currentRepoPath: repoPath, comments: {}, draft: null });
+      await get().loadComments();
+    },
 
-      setRepoPath: (repoPath) => set({ currentRepoPath: repoPath }),
+    loadComments: async () => {
+      const { currentRepoPath, isDemo } = get();
+      if (!currentRepoPath || isDemo) return;
+
+      try {
+        await migrateLegacyComments(currentRepoPath);
+        const comments = await invoke<Comment[]>("list_comments", {
+          repoPath: currentRepoPath,
+        });
+        // Ignore the result if another repo was opened meanwhile
+        if (get().currentRepoPath === currentRepoPath) {
+          set({ comments: groupByFile(comments) });
+        }
+      } catch (err) {
+        console.error("Failed to load comments:", err);
+      }
+    },
 
-      addComment: (
+    addComment: async (
+      filePath,
+      startLine,
+      endLine,
+      content,
+      category,
+      codeSnippet,
+      isOld,
+      staged,
+      suggestion,
+    ) => {
+      const { currentRepoPath, isDemo } = get();
+      if (!currentRepoPath) return;
+
+      const comment: Comment = {
+        id: crypto.randomUUID(),
         filePath,
         startLine,
         endLine,
         content,
         category,
         codeSnippet,
+        createdAt: Date.now(),
         isOld,
-      ) => {
-        const { currentRepoPath } = get();
-        if (!currentRepoPath) return;
-
-        const comment: Comment = {
-          id: crypto.randomUUID(),
-          filePath,
-          startLine,
-          endLine,
-          content,
-          category,
-          codeSnippet,
-          createdAt: Date.now(),
-          isOld,
-        };
+        staged,
+        suggestion,
+      };
 
+      try {
+        if (!isDemo) {
+          await invoke("add_comment", { repoPath: currentRepoPath, comment });
+        }
         set((state) => ({
           comments: {
             ...state.comments,
-            [currentRepoPath]: {
-              ...(state.comments[currentRepoPath] || {}),
-              [filePath]: [
-                ...(state.comments[currentRepoPath]?.[filePath] || []),
-                comment,
-              ],
-            },
+            [filePath]: [...(state.comments[filePath] || []), comment],
           },
           draft: null,
         }));
-      },
+      } catch (err) {
+        console.error("Failed to add comment:", err);
+      }
+    },
 
-      removeComment: (filePath, commentId) => {
-        const { currentRepoPath } = get();
-        if (!currentRepoPath) return;
+    removeComment: async (filePath, commentId) => {
+      const { currentRepoPath, isDemo } = get();
+      if (!currentRepoPath) return;
 
+      try {
+        if (!isDemo) {
+          await invoke("delete_comment", {
+            repoPath: currentRepoPath,
+            id: commentId,
+          });
+        }
         set((state) => ({
           comments: {
             ...state.comments,
-            [currentRepoPath]: {
-              ...(state.comments[currentRepoPath] || {}),
-              [filePath]: (
-                state.comments[currentRepoPath]?.[filePath] || []
-              ).filter((c) => c.id !== commentId),
-            },
+            [filePath]: (state.comments[filePath] || []).filter(
+              (c) => c.id !== commentId,
+            ),
           },
         }));
-      },
+      } catch (err) {
+        console.error("Failed to delete comment:", err);
+      }
+    },
 
-      updateComment: (filePath, commentId, content, category) => {
-        const { currentRepoPath } = get();
-        if (!currentRepoPath) return;
+    updateComment: async (
+      filePath,
+      commentId,
+      content,
+      category,
+      suggestion,
+    ) => {
+      const { currentRepoPath, isDemo } = get();
+      if (!currentRepoPath) return;
 
+      try {
+        if (!isDemo) {
+          await invoke("update_comment", {
+            repoPath: currentRepoPath,
+            id: commentId,
+            content,
+            category,
+            suggestion: suggestion ?? null,
+          });
+        }
         set((state) => ({
           comments: {
             ...state.comments,
-            [currentRepoPath]: {
-              ...(state.comments[currentRepoPath] || {}),
-              [filePath]: (
-                state.comments[currentRepoPath]?.[filePath] || []
-              ).map((c) =>
-                c.id === commentId ? { ...c, content, category } : c,
-              ),
-            },
+            [filePath]: (state.comments[filePath] || []).map((c) =>
+              c.id === commentId ? { ...c, content, category, suggestion } : c,
+            ),
           },
         }));
-      },
+      } catch (err) {
+        console.error("Failed to update comment:", err);
+      }
+    },
 
-      getFileComments: (filePath) => {
-        const { currentRepoPath, comments } = get();
-        if (!currentRepoPath) return [];
-        return comments[currentRepoPath]?.[filePath] || [];
-      },
+    applySuggestion: async (comment) => {
+      const { currentRepoPath, isDemo } = get();
+      if (!currentRepoPath || isDemo) return;
 
-      getAllComments: () => {
-        const { currentRepoPath, comments } = get();
-        if (!currentRepoPath) return [];
-        const repoComments = comments[currentRepoPath] || {};
-        return Object.values(repoComments)
-          .flat()
-          .sort((a, b) => {
-            if (a.filePath !== b.filePath)
-              return a.filePath.localeCompare(b.filePath);
-            return a.startLine - b.startLine;
-          });
-      },
+      replaceComment(
+        await invoke<Comment>("apply_suggestion", {
+          repoPath: currentRepoPath,
+          commentId: comment.id,
+        }),
+      );
+    },
 
-      setDraft: (draft) => set({ draft }),
+    undoSuggestion: async (comment) => {
+      const { currentRepoPath, isDemo } = get();
+      if (!currentRepoPath || isDemo) return;
 
-      clearAllComments: () => {
-        const { currentRepoPath } = get();
-        if (!currentRepoPath) return;
+      replaceComment(
+        await invoke<Comment>("undo_suggestion", {
+          repoPath: currentRepoPath,
+          commentId: comment.id,
+        }),
+      );
+    },
 
-        set((state) => ({
-          comments: {
-            ...state.comments,
-            [currentRepoPath]: {},
-          },
-        }));
-      },
+    addReply: async (comment, content) => {
+      const { currentRepoPath, isDemo } = get();
+      if (!currentRepoPath) return;
 
-      initDemoComments: (repoPath: string, comments: Record<string, Comment[]>) => {
-        set({
-          currentRepoPath: repoPath,
-          comments: {
-            [repoPath]: comments,
-          },
+      const reply: CommentReply = {
+        id: crypto.randomUUID(),
+        author: "reviewer",
+        content,
+        createdAt: Date.now(),
+      };
+
+      try {
+        replaceComment(
+          isDemo
+            ? {
+                ...comment,
+                replies: [...(comment.replies || []), reply],
+                resolved: false,
+              }
+            : await invoke<Comment>("add_comment_reply", {
+                repoPath: currentRepoPath,
+                commentId: comment.id,
+                reply,
+              }),
+        );
+      } catch (err) {
+        console.error("Failed to add reply:", err);
+      }
+    },
+
+    removeReply: async (comment, replyId) => {
+      const { currentRepoPath, isDemo } = get();
+      if (!currentRepoPath) return;
+
+      try {
+        replaceComment(
+          isDemo
+            ? {
+                ...comment,
+                replies: (comment.replies || []).filter((r) => r.id !== replyId),
+              }
+            : await invoke<Comment>("delete_comment_reply", {
+                repoPath: currentRepoPath,
+                commentId: comment.id,
+                replyId,
+              }),
+        );
+      } catch (err) {
+        console.error("Failed to delete reply:", err);
+      }
+    },
+
+    setResolved: async (comment, resolved) => {
+      const { currentRepoPath, isDemo } = get();
+      if (!currentRepoPath) return;
+
+      try {
+        replaceComment(
+          isDemo
+            ? { ...comment, resolved }
+            : await invoke<Comment>("resolve_comment", {
+                repoPath: currentRepoPath,
+                id: comment.id,
+                resolved,
+              }),
+        );
+      } catch (err) {
+        console.error("Failed to resolve comment:", err);
+      }
+    },
+
+    getFileComments: (filePath) => {
+      return get().comments[filePath] || [];
+    },
+
+    getAllComments: () => {
+      return Object.values(get().comments)
+        .flat()
+        .sort((a, b) => {
+          if (a.filePath !== b.filePath)
+            return a.filePath.localeCompare(b.filePath);
+          return a.startLine - b.startLine;
         });
-      },
+    },
+
+    setDraft: (draft) => set({ draft }),
 
-      exportToMarkdown: () => {
-        const allComments = get().getAllComments();
-        if (allComments.length === 0) return "";
-
-        // Group by category
-        const byCategory: Record<CommentCategory, Comment[]> = {
-          issue: [],
-          suggestion: [],
-          question: [],
-          nitpick: [],
-          praise: [],
-        };
-
-        for (const comment of allComments) {
-          byCategory[comment.category].push(comment);
+    clearAllComments: async () => {
+      const { currentRepoPath, isDemo } = get();
+      if (!currentRepoPath) return;
+
+      try {
+        if (!isDemo) {
+          await invoke("clear_comments", { repoPath: currentRepoPath });
         }
+        set({ comments: {} });
+      } catch (err) {
+        console.error("Failed to clear comments:", err);
+      }
+    },
 
-        const actionRequired =
-          byCategory.issue.length + byCategory.suggestion.length;
-        const s = (n: number) => (n !== 1 ? "s" : "");
-
-        // Build XML-structured output
-        let output = `<revu-review>
-The user has reviewed code changes and left ${allComments.length} comment${s(allComments.length)}. Process each comment according to its category.
-
-<review-categories>
-- ISSUE: Bug or error — must be fixed
-- SUGGESTION: Improvement — implement unless problematic
-- QUESTION: Clarification needed — explain your reasoning
-- NITPICK: Minor preference — fix if easy
-- PRAISE: Positive feedback — no change needed
-</review-categories>
-
-<review-summary>
-Total: ${allComments.length} comment${s(allComments.length)}
-Action required: ${actionRequired}${actionRequired > 0 ? ` (${byCategory.issue.length} issue${s(byCategory.issue.length)}, ${byCategory.suggestion.length} suggestion${s(byCategory.suggestion.length)})` : ""}
-Questions: ${byCategory.question.length}
-</review-summary>
-
-`;
-
-        // Add each comment as structured XML
-        allComments.forEach((comment, index) => {
-          const lang = getLanguageFromPath(comment.filePath);
-          const side = comment.isOld ? "old" : "new";
-          const lineRef =
-            comment.startLine === comment.endLine
-              ? comment.startLine.toString()
-              : `${comment.startLine}-${comment.endLine}`;
-
-          output += `<comment id="${index + 1}">
-<file>${comment.filePath}</file>
-<line>${lineRef}</line>
-<side>${side}</side>
-<category>${comment.category}</category>
-${
-  comment.codeSnippet
-    ? `<code language="${lang}">
-${comment.codeSnippet}</code>
-`
-    : ""
-}<text>${comment.content}</text>
-</comment>
-
-`;
-        });
+    initDemoComments: (repoPath: string, comments: Record<string, Comment[]>) => {
+      set({
+        currentRepoPath: repoPath,
+        comments,
+        isDemo: true,
+      });
+    },
 
-        output += `</revu-review>`;
-        return output.trim();
-      },
-    }),
-    {
-      name: "revu-comments",
-      partialize: (state) => ({ comments: state.comments }),
+    renderReview: async (format, options) => {
+      const { currentRepoPath, getAllComments } = get();
+      const allComments = getAllComments();
+      if (!currentRepoPath || allComments.length === 0) return "";
+      return invoke<string>("render_review", {
+        repoPath: currentRepoPath,
+        comments: allComments,
+        format: format ?? null,
+        options: options ?? null,
+      });
     },
-  ),
-);
+  };
+});
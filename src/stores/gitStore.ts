@@ -1,6 +1,37 @@
 import { create } from "zustand";
 import { invoke } from "@tauri-apps/api/core";
-import type { FileEntry, FileDiff, RepositoryStatus } from "@/types/git";
+import { useUiStore } from "@/stores/uiStore";
+import type {
+  DiffHunk,
+  DiffSettings,
+  DirComparison,
+  FileEntry,
+  FileDiff,
+  ImportedPatch,
+  OpenedPatchReview,
+  PatchApplyResult,
+  PatchTarget,
+  RepoChanged,
+  RepoSettled,
+  RepositoryStatus,
+} from "@/types/git";
+
+// Keys for requests that supersede each other, so only the latest one finishes
+const STATUS_REQUEST = "status";
+const DIFF_REQUEST = "file-diff";
+
+// The backend rejects a superseded request with this error; it isn't worth showing
+const isCancelled = (e: unknown) => String(e) === "Request cancelled";
+
+// The toolbar toggles plus the diff options chosen in the UI store
+const diffOptions = (
+  fullContext: boolean,
+  ignoreWhitespace: boolean,
+): DiffSettings => ({
+  ...useUiStore.getState().diffSettings,
+  contextLines: fullContext ? 999999 : undefined,
+  ignoreWhitespace,
+});
 
 interface DemoState {
   status: RepositoryStatus;
@@ -16,6 +47,14 @@ interface GitState {
   error: string | null;
   isDemo: boolean;
   _demoState: DemoState | null;
+  // A patch being previewed instead of the working tree changes
+  patchPreview: ImportedPatch | null;
+  // Set when reviewing a patch without a repository
+  patchName: string | null;
+  // Set when comparing two directories instead of a repository
+  comparison: DirComparison | null;
+  // True between a change made outside revu and the repository settling
+  isChanging: boolean;
 
   setRepoPath: (path: string) => Promise<void>;
   refreshStatus: () => Promise<void>;
@@ -25,6 +64,12 @@ interface GitState {
     ignoreWhitespace?: boolean,
   ) => Promise<void>;
   fetchDiff: (fullContext: boolean, ignoreWhitespace: boolean) => Promise<void>;
+  loadHunk: (
+    hunkIndex: number,
+    fullContext: boolean,
+    ignoreWhitespace: boolean,
+  ) => Promise<void>;
+  loadFullDiff: (fullContext: boolean, ignoreWhitespace: boolean) => Promise<void>;
   stageFile: (filePath: string) => Promise<void>;
   unstageFile: (filePath: string) => Promise<void>;
   stageAll: () => Promise<void>;
@@ -33,6 +78,23 @@ interface GitState {
   discardFile: (filePath: string) => Promise<void>;
   discardAll: () => Promise<void>;
   clearError: () => void;
+  importPatch: (source: { patch?: string; path?: string }) => Promise<void>;
+  applyPatch: (target: PatchTarget) => Promise<PatchApplyResult>;
+  closePatch: () => void;
+  openPatchReview: (source: { patch?: string; path?: string }) => Promise<void>;
+  initPatchReview: (opened: OpenedPatchReview) => void;
+  openComparison: (
+    left: string,
+    right: string,
+    excludes?: string[],
+  ) => Promise<void>;
+  initComparison: (comparison: DirComparison) => Promise<void>;
+  handleRepoChanged: (
+    event: RepoChanged,
+    fullContext: boolean,
+    ignoreWhitespace: boolean,
+  ) => Promise<void>;
+  handleRepoSettled: (event: RepoSettled) => void;
   // Demo mode - accepts pre-built demo state
   initDemoMode: (demoState: DemoState) => void;
 }
@@ -46,6 +108,10 @@ export const useGitStore = create<GitState>()((set, get) => ({
   error: null,
   isDemo: false,
   _demoState: null,
+  patchPreview: null,
+  patchName: null,
+  comparison: null,
+  isChanging: false,
 
   initDemoMode: (demoState: DemoState) => {
     const { status, diffs } = demoState;
@@ -68,26 +134,42 @@ export const useGitStore = create<GitState>()((set, get) => ({
     const { isDemo } = get();
     if (isDemo) return; // Ignore in demo mode
 
-    set({ repoPath: path, isLoading: true, error: null });
+    set({
+      repoPath: path,
+      patchPreview: null,
+      patchName: null,
+      comparison: null,
+      isChanging: false,
+      selectedFile: null,
+      currentDiff: null,
+      isLoading: true,
+      error: null,
+    });
     try {
       const status = await invoke<RepositoryStatus>("get_status", {
         repoPath: path,
+        request: STATUS_REQUEST,
       });
       set({ status, isLoading: false });
     } catch (e) {
+      if (isCancelled(e)) return;
       set({ error: String(e), isLoading: false });
     }
   },
 
   refreshStatus: async () => {
-    const { repoPath, isDemo } = get();
-    if (!repoPath || isDemo) return; // Ignore in demo mode
+    const { repoPath, isDemo, patchName } = get();
+    if (!repoPath || isDemo || patchName) return; // Nothing to refresh without a repository
 
     set({ isLoading: true, error: null });
     try {
-      const status = await invoke<RepositoryStatus>("get_status", { repoPath });
+      const status = await invoke<RepositoryStatus>("get_status", {
+        repoPath,
+        request: STATUS_REQUEST,
+      });
       set({ status, isLoading: false });
     } catch (e) {
+      if (isCancelled(e)) return;
       set({ error: String(e), isLoading: false });
     }
   },
@@ -97,12 +179,19 @@ export const useGitStore = create<GitState>()((set, get) => ({
     fullContext = false,
     ignoreWhitespace = false,
   ) => {
-    const { repoPath, isDemo, _demoState } = get();
+    const { repoPath, isDemo, _demoState, patchPreview } = get();
     if (!repoPath) return;
 
     set({ selectedFile: file, currentDiff: null });
 
     if (file) {
+      // A previewed patch carries its own diffs
+      if (patchPreview) {
+        const diff = patchPreview.files.find((f) => f.path === file.path);
+        set({ currentDiff: diff || null });
+        return;
+      }
+
       // In demo mode, use pre-built diff data
       if (isDemo && _demoState) {
         const diff = _demoState.diffs[file.path] || null;
@@ -115,19 +204,23 @@ export const useGitStore = create<GitState>()((set, get) => ({
           repoPath,
           filePath: file.path,
           staged: file.staged,
-          contextLines: fullContext ? 999999 : null,
-          ignoreWhitespace: ignoreWhitespace || null,
+          options: diffOptions(fullContext, ignoreWhitespace),
+          request: DIFF_REQUEST,
         });
         set({ currentDiff: diff });
       } catch (e) {
+        if (isCancelled(e)) return;
         set({ error: String(e) });
       }
+    } else if (!isDemo) {
+      // Don't let a diff still loading for the previous file land after deselecting it
+      invoke("cancel_request", { request: DIFF_REQUEST }).catch(() => {});
     }
   },
 
   fetchDiff: async (fullContext: boolean, ignoreWhitespace: boolean) => {
-    const { repoPath, selectedFile, isDemo, _demoState } = get();
-    if (!repoPath || !selectedFile) return;
+    const { repoPath, selectedFile, isDemo, _demoState, patchPreview } = get();
+    if (!repoPath || !selectedFile || patchPreview) return;
 
     // In demo mode, just return the existing diff
     if (isDemo && _demoState) {
@@ -141,11 +234,68 @@ export const useGitStore = create<GitState>()((set, get) => ({
         repoPath,
         filePath: selectedFile.path,
         staged: selectedFile.staged,
-        contextLines: fullContext ? 999999 : null,
-        ignoreWhitespace: ignoreWhitespace || null,
+        options: diffOptions(fullContext, ignoreWhitespace),
+        request: DIFF_REQUEST,
+      });
+      set({ currentDiff: diff });
+    } catch (e) {
+      if (isCancelled(e)) return;
+      set({ error: String(e) });
+    }
+  },
+
+  loadHunk: async (
+    hunkIndex: number,
+    fullContext: boolean,
+    ignoreWhitespace: boolean,
+  ) => {
+    const { repoPath, selectedFile, currentDiff } = get();
+    if (!repoPath || !selectedFile || !currentDiff) return;
+    // Each call loads the next page of the hunk's lines
+    const offset = currentDiff.hunks[hunkIndex]?.lines.length ?? 0;
+
+    try {
+      const page = await invoke<DiffHunk>("get_diff_hunk", {
+        repoPath,
+        filePath: selectedFile.path,
+        staged: selectedFile.staged,
+        hunkIndex,
+        options: diffOptions(fullContext, ignoreWhitespace),
+        offset,
+      });
+      // The user may have moved on to another file, or loaded this page already
+      const diff = get().currentDiff;
+      if (diff?.path !== currentDiff.path) return;
+      if (diff.hunks[hunkIndex]?.lines.length !== offset) return;
+      set({
+        currentDiff: {
+          ...diff,
+          hunks: diff.hunks.map((h, i) =>
+            i === hunkIndex ? { ...page, lines: [...h.lines, ...page.lines] } : h,
+          ),
+        },
+      });
+    } catch (e) {
+      set({ error: String(e) });
+    }
+  },
+
+  loadFullDiff: async (fullContext: boolean, ignoreWhitespace: boolean) => {
+    const { repoPath, selectedFile } = get();
+    if (!repoPath || !selectedFile) return;
+
+    try {
+      const diff = await invoke<FileDiff>("get_file_diff", {
+        repoPath,
+        filePath: selectedFile.path,
+        staged: selectedFile.staged,
+        options: diffOptions(fullContext, ignoreWhitespace),
+        full: true,
+        request: DIFF_REQUEST,
       });
       set({ currentDiff: diff });
     } catch (e) {
+      if (isCancelled(e)) return;
       set({ error: String(e) });
     }
   },
@@ -252,4 +402,149 @@ export const useGitStore = create<GitState>()((set, get) => ({
   },
 
   clearError: () => set({ error: null }),
+
+  importPatch: async (source: { patch?: string; path?: string }) => {
+    const { repoPath, isDemo } = get();
+    if (!repoPath || isDemo) return;
+
+    try {
+      const imported = await invoke<ImportedPatch>("import_patch", {
+        patch: source.patch ?? null,
+        path: source.path ?? null,
+      });
+      const first = imported.files[0];
+      set({
+        patchPreview: imported,
+        selectedFile: { path: first.path, status: first.status, staged: false },
+        currentDiff: first,
+        error: null,
+      });
+    } catch (e) {
+      set({ error: String(e) });
+    }
+  },
+
+  applyPatch: async (target: PatchTarget) => {
+    const { repoPath, patchPreview, refreshStatus } = get();
+    if (!repoPath || !patchPreview) throw new Error("No patch to apply");
+
+    const result = await invoke<PatchApplyResult>("apply_patch", {
+      repoPath,
+      patch: patchPreview.patch,
+      target,
+    });
+    if (result.applied) {
+      set({ patchPreview: null, selectedFile: null, currentDiff: null });
+      await refreshStatus();
+    }
+    return result;
+  },
+
+  closePatch: () =>
+    set({ patchPreview: null, selectedFile: null, currentDiff: null }),
+
+  openPatchReview: async (source: { patch?: string; path?: string }) => {
+    if (get().isDemo) return;
+
+    try {
+      const opened = await invoke<OpenedPatchReview>("open_patch_review", {
+        patch: source.patch ?? null,
+        path: source.path ?? null,
+      });
+      get().initPatchReview(opened);
+    } catch (e) {
+      set({ error: String(e) });
+    }
+  },
+
+  initPatchReview: (opened: OpenedPatchReview) => {
+    const files: FileEntry[] = opened.files.map((f) => ({
+      path: f.path,
+      oldPath: f.oldPath,
+      status: f.status,
+      staged: false,
+    }));
+    const first = opened.files[0];
+
+    set({
+      repoPath: opened.path,
+      patchName: opened.name,
+      comparison: null,
+      patchPreview: { patch: opened.patch, files: opened.files },
+      status: {
+        path: opened.path,
+        files,
+        stagedCount: 0,
+        unstagedCount: files.length,
+      },
+      selectedFile: files[0] || null,
+      currentDiff: first || null,
+      isLoading: false,
+      error: null,
+    });
+  },
+
+  openComparison: async (left: string, right: string, excludes = []) => {
+    if (get().isDemo) return;
+
+    try {
+      const comparison = await invoke<DirComparison>("open_comparison", {
+        left,
+        right,
+        excludes,
+      });
+      await get().initComparison(comparison);
+    } catch (e) {
+      set({ error: String(e) });
+    }
+  },
+
+  // The backend serves status and diffs for the comparison's path like a repository's
+  initComparison: async (comparison: DirComparison) => {
+    set({
+      repoPath: comparison.path,
+      comparison,
+      patchPreview: null,
+      patchName: null,
+      status: null,
+      selectedFile: null,
+      currentDiff: null,
+    });
+    await get().refreshStatus();
+  },
+
+  // Refreshes status, and the open diff only if its file (or the index) changed
+  handleRepoChanged: async (
+    event: RepoChanged,
+    fullContext: boolean,
+    ignoreWhitespace: boolean,
+  ) => {
+    const { repoPath, refreshStatus, fetchDiff } = get();
+    if (event.repository !== repoPath) return;
+
+    set({ isChanging: true });
+    await refreshStatus();
+
+    const { selectedFile, status, patchPreview } = get();
+    if (!selectedFile || patchPreview) return;
+    const affected =
+      event.paths.some(
+        (p) => p === selectedFile.path || selectedFile.path.startsWith(`${p}/`),
+      ) || event.git;
+    if (!affected) return;
+
+    // The file may have left this section, e.g. staged by the agent
+    const stillListed = status?.files.some(
+      (f) => f.path === selectedFile.path && f.staged === selectedFile.staged,
+    );
+    if (stillListed) {
+      await fetchDiff(fullContext, ignoreWhitespace);
+    }
+  },
+
+  handleRepoSettled: (event: RepoSettled) => {
+    if (event.repository === get().repoPath) {
+      set({ isChanging: false });
+    }
+  },
 }));
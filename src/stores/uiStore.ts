@@ -1,5 +1,7 @@
 import { create } from "zustand";
 import { persist } from "zustand/middleware";
+import type { ExportFormat, ExportOptions } from "@/types/review";
+import type { DiffSettings } from "@/types/git";
 
 export type DiffViewMode = "split" | "unified";
 export type Theme = "light" | "dark" | "system";
@@ -14,8 +16,13 @@ interface UiState {
   theme: Theme;
   sidebarWidth: number;
   showCommentsPanel: boolean;
+  showHistoryPanel: boolean;
   showFullFileContext: boolean;
   ignoreWhitespace: boolean;
+  // Diff options other than context and ignoring all whitespace, which have toolbar toggles
+  diffSettings: DiffSettings;
+  exportFormat: ExportFormat;
+  exportOptions: ExportOptions;
   scrollToLine: ScrollToLine | null;
 
   setDiffViewMode: (mode: DiffViewMode) => void;
@@ -23,8 +30,12 @@ interface UiState {
   setSidebarWidth: (width: number) => void;
   toggleCommentsPanel: () => void;
   setShowCommentsPanel: (show: boolean) => void;
+  toggleHistoryPanel: () => void;
   setShowFullFileContext: (show: boolean) => void;
   setIgnoreWhitespace: (ignore: boolean) => void;
+  setDiffSettings: (settings: Partial<DiffSettings>) => void;
+  setExportFormat: (format: ExportFormat) => void;
+  setExportOptions: (options: Partial<ExportOptions>) => void;
   setScrollToLine: (target: ScrollToLine | null) => void;
 }
 
@@ -35,8 +46,12 @@ export const useUiStore = create<UiState>()(
       theme: "system",
       sidebarWidth: 280,
       showCommentsPanel: false,
+      showHistoryPanel: false,
       showFullFileContext: false,
       ignoreWhitespace: false,
+      diffSettings: {},
+      exportFormat: "xml",
+      exportOptions: { includeHunks: false, hunkContextLines: 3 },
       scrollToLine: null,
 
       setDiffViewMode: (mode) => set({ diffViewMode: mode }),
@@ -46,8 +61,19 @@ export const useUiStore = create<UiState>()(
       toggleCommentsPanel: () =>
         set((state) => ({ showCommentsPanel: !state.showCommentsPanel })),
       setShowCommentsPanel: (show) => set({ showCommentsPanel: show }),
+      toggleHistoryPanel: () =>
+        set((state) => ({ showHistoryPanel: !state.showHistoryPanel })),
       setShowFullFileContext: (show) => set({ showFullFileContext: show }),
       setIgnoreWhitespace: (ignore) => set({ ignoreWhitespace: ignore }),
+      setDiffSettings: (settings) =>
+        set((state) => ({
+          diffSettings: { ...state.diffSettings, ...settings },
+        })),
+      setExportFormat: (format) => set({ exportFormat: format }),
+      setExportOptions: (options) =>
+        set((state) => ({
+          exportOptions: { ...state.exportOptions, ...options },
+        })),
       setScrollToLine: (target) => set({ scrollToLine: target }),
     }),
     {
@@ -59,7 +85,10 @@ export const useUiStore = create<UiState>()(
         showCommentsPanel: state.showCommentsPanel,
         showFullFileContext: state.showFullFileContext,
         ignoreWhitespace: state.ignoreWhitespace,
-        // Note: scrollToLine is intentionally not persisted
+        diffSettings: state.diffSettings,
+        exportFormat: state.exportFormat,
+        exportOptions: state.exportOptions,
+        // Note: scrollToLine and showHistoryPanel are intentionally not persisted
       }),
     },
   ),
@@ -5,6 +5,23 @@ export type CommentCategory =
   | "nitpick"
   | "praise";
 
+export type ReplyStatus = "addressed" | "disputed" | "answered";
+
+export type ReplyAuthor = "reviewer" | "agent";
+
+export interface CommentReply {
+  id: string;
+  author: ReplyAuthor;
+  content: string;
+  createdAt: number;
+  status?: ReplyStatus; // only on agent replies
+}
+
+export interface AppliedSuggestion {
+  reversePatch: string;
+  appliedAt: number;
+}
+
 export interface Comment {
   id: string;
   filePath: string;
@@ -15,6 +32,11 @@ export interface Comment {
   codeSnippet: string;
   createdAt: number;
   isOld: boolean; // true = old/deletion side, false = new/addition side
+  staged?: boolean; // true = made on the staged (HEAD → index) diff
+  suggestion?: string; // replacement text for the commented lines (new side only)
+  appliedSuggestion?: AppliedSuggestion; // set once written to the working tree
+  replies?: CommentReply[]; // thread with the agent, oldest first
+  resolved?: boolean; // resolved threads are not exported
 }
 
 export interface CommentDraft {
@@ -23,8 +45,10 @@ export interface CommentDraft {
   endLine: number;
   codeSnippet: string;
   isOld: boolean; // true = old/deletion side, false = new/addition side
+  staged?: boolean;
   // Edit mode fields
   editingId?: string;
   existingContent?: string;
   existingCategory?: CommentCategory;
+  existingSuggestion?: string;
 }
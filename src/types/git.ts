@@ -22,6 +22,19 @@ export interface DiffLine {
   content: string;
   oldLineNo?: number;
   newLineNo?: number;
+  // Last line of its side, without a newline at the end
+  noNewline?: boolean;
+  // Part of a block moved elsewhere in the diff
+  moved?: LineMove;
+}
+
+// The other end of a moved line: where an added line was deleted, or a deleted line added
+export interface LineMove {
+  path: string;
+  // Old line number for a deleted counterpart, new for an added one
+  line: number;
+  // The block changed slightly as it moved
+  edited: boolean;
 }
 
 export interface DiffHunk {
@@ -30,7 +43,10 @@ export interface DiffHunk {
   oldLines: number;
   newStart: number;
   newLines: number;
+  // Empty for a hunk of a large diff that hasn't been loaded yet
   lines: DiffLine[];
+  // Lines of a large diff's hunk after `lines` that haven't been loaded yet
+  omittedLines?: number;
 }
 
 export interface FileDiff {
@@ -40,6 +56,32 @@ export interface FileDiff {
   hunks: DiffHunk[];
   isBinary: boolean;
   language?: string;
+  // Over the size limits: hunks arrive as summaries and are loaded on request
+  isLarge?: boolean;
+  // The encoding both sides were decoded from, e.g. "UTF-8" or "Shift_JIS"
+  encoding?: string;
+  hasBom?: boolean;
+  // Git file modes, e.g. 0o100644, 0o100755 (executable) or 0o120000 (symlink)
+  oldMode?: number;
+  newMode?: number;
+  oldLinkTarget?: string;
+  newLinkTarget?: string;
+  // Every change is CRLF/LF or a newline at the end of the file
+  lineEndingsOnly?: boolean;
+}
+
+export type DiffAlgorithm = "myers" | "minimal" | "patience" | "histogram";
+
+// How a diff is computed; unset fields follow the user's git config
+export interface DiffSettings {
+  contextLines?: number;
+  algorithm?: DiffAlgorithm;
+  indentHeuristic?: boolean;
+  ignoreWhitespace?: boolean;
+  ignoreWhitespaceChange?: boolean;
+  ignoreWhitespaceEol?: boolean;
+  ignoreBlankLines?: boolean;
+  detectMoves?: boolean;
 }
 
 export interface RepositoryStatus {
@@ -49,3 +91,48 @@ export interface RepositoryStatus {
   stagedCount: number;
   unstagedCount: number;
 }
+
+export type PatchTarget = "workdir" | "index" | "both";
+
+export interface ImportedPatch {
+  patch: string;
+  files: FileDiff[];
+}
+
+export interface PatchConflict {
+  path: string;
+  hunk?: string;
+  message: string;
+}
+
+export interface PatchApplyResult {
+  applied: boolean;
+  conflicts: PatchConflict[];
+}
+
+// A patch reviewed without a repository; `path` stands in for the repository path
+export interface OpenedPatchReview extends ImportedPatch {
+  id: string;
+  name: string;
+  path: string;
+}
+
+// Two directory trees compared instead of a repository; `path` stands in for the repository path
+export interface DirComparison {
+  id: string;
+  name: string;
+  left: string;
+  right: string;
+  excludes: string[];
+  path: string;
+}
+
+export interface RepoChanged {
+  repository: string;
+  paths: string[];
+  git: boolean;
+}
+
+export interface RepoSettled {
+  repository: string;
+}
@@ -0,0 +1,84 @@
+import type { Comment, ReplyStatus } from "./comment";
+
+export type ExportFormat = "xml" | "json" | "markdown" | "sarif" | "patch";
+
+export const exportFormatLabels: Record<ExportFormat, string> = {
+  xml: "Agent (XML)",
+  json: "JSON",
+  markdown: "Markdown",
+  sarif: "SARIF",
+  patch: "Annotated patch",
+};
+
+export interface ExportOptions {
+  includeHunks: boolean;
+  hunkContextLines: number;
+}
+
+export type Freshness =
+  | { state: "current" }
+  | { state: "relocated"; fromStartLine: number; fromEndLine: number }
+  | { state: "ambiguous"; candidateLines: number[] }
+  | { state: "stale" };
+
+export interface StaleWarning {
+  commentId: string;
+  filePath: string;
+  startLine: number;
+  endLine: number;
+  freshness: Freshness;
+  message: string;
+}
+
+export interface DeliveryReport {
+  destination: string;
+  success: boolean;
+  message: string;
+  path?: string;
+}
+
+export interface ExportResult {
+  path?: string;
+  deliveries: DeliveryReport[];
+}
+
+export interface ReviewSummary {
+  total: number;
+  actionRequired: number;
+  issues: number;
+  suggestions: number;
+  questions: number;
+  nitpicks: number;
+  praise: number;
+}
+
+export interface ReviewMetadata {
+  id: string;
+  repository: string;
+  repoName: string;
+  createdAt: number;
+  branch: string | null;
+  head: string | null;
+  format: ExportFormat;
+  summary: ReviewSummary;
+  paths: string[];
+}
+
+export interface ReviewHistoryEntry extends ReviewMetadata {
+  comments: Comment[];
+  content: string | null;
+}
+
+export interface IncomingReply {
+  commentId: string;
+  status: ReplyStatus;
+  message: string;
+}
+
+/** Payload of the `agent-replies` event. */
+export interface ReplyBatch {
+  reviewId: string;
+  repository: string | null; // null when the review wasn't exported through revu
+  replies: IncomingReply[];
+  matched: number;
+}